@@ -1,16 +1,132 @@
-use std::path::Path;
-use clap::Parser;
+use std::path::{Path, PathBuf};
+use clap::{Args, Parser, Subcommand};
+use momi::config::format::ConfigFormat;
+use momi::config::front_matter::FrontMatter;
+use momi::export::ExportFormat;
+use momi::metadata::options::NewOptions;
 
+/// The command line of momi.
+///
+/// `momi <filename>` without a subcommand is the same as `momi new <filename>`.
 #[derive(Parser, Debug, Default)]
-#[command(version, about, long_about)]
+#[command(version, about, long_about, arg_required_else_help = true, subcommand_negates_reqs = true)]
 pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+    #[command(flatten)]
+    pub new: Option<NewArgs>,
+    #[arg(short, long, global = true, default_value_t = false)]
+    pub verbose: bool,
+    /// The config file overriding the other config files. Defaults to the MOMI_CONFIG environment variable
+    #[arg(long, global = true)]
+    pub config: Option<PathBuf>,
+    /// The profile overriding the config. Defaults to the MOMI_PROFILE environment variable
+    #[arg(long, global = true)]
+    pub profile: Option<String>,
+    /// Deprecated alias of `momi config create`, kept for the scripts written before the subcommands
+    #[arg(long, hide = true, exclusive = true, default_value_t = false)]
+    pub create_config: bool,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Create a new document
+    New(NewArgs),
+    /// List the documents in a directory and its subdirectories with their date, author and title
+    List {
+        /// The directory to list. Defaults to the current directory
+        dir: Option<PathBuf>,
+    },
+    /// Show the metadata of a document
+    Show {
+        /// The document to show
+        file: PathBuf,
+    },
+    /// Open a document with the default program
+    Edit {
+        /// The document to open
+        file: PathBuf,
+    },
+    /// Export documents to another format, such as a standalone HTML page
+    Export {
+        /// The documents to export
+        #[arg(required = true)]
+        files: Vec<PathBuf>,
+        /// The format to export to
+        #[arg(long, value_enum)]
+        format: ExportFormat,
+        /// The exported file. Defaults to the document with the extension of the format,
+        /// or to stdout for ndjson
+        #[arg(long, conflicts_with = "stdout")]
+        output: Option<PathBuf>,
+        /// Overwrite the exported file if it already exists
+        #[arg(short, long, default_value_t = false)]
+        overwrite: bool,
+        /// Print the exported document to stdout instead of writing it to a file
+        #[arg(long, default_value_t = false)]
+        stdout: bool,
+    },
+    /// Create a project with a .momi directory, whose minutes are written to its minutes directory
+    Init {
+        /// The root directory of the project. Defaults to the current directory
+        dir: Option<PathBuf>,
+    },
+    /// Manage the configuration file
+    #[command(subcommand)]
+    Config(ConfigCommand),
+}
+
+#[derive(Subcommand, Debug)]
+pub enum ConfigCommand {
+    /// Create a default configuration file
+    Create,
+    /// Print the value of a setting, or tell on stderr that it is not set
+    Get {
+        /// The key of the setting
+        key: String,
+    },
+    /// Set the value of a setting. Structured settings such as sections are given as JSON
+    Set {
+        /// The key of the setting
+        key: String,
+        /// The new value of the setting
+        value: String,
+    },
+    /// Remove a setting so its default value is used
+    Unset {
+        /// The key of the setting
+        key: String,
+    },
+    /// Print the settings that are set
+    List,
+    /// Print the path of the configuration file changed by `momi config`
+    Path {
+        /// Print the config files of every layer, from the lowest to the highest precedence
+        #[arg(long, default_value_t = false)]
+        all: bool,
+    },
+    /// Check the configuration file for problems
+    Validate,
+    /// List the names of the profiles
+    Profiles,
+    /// Upgrade the configuration file to the current version, keeping the original as a `.v<version>.bak` file
+    Migrate,
+    /// Convert the configuration file to another format, keeping the original as a .bak file
+    Convert {
+        /// The format to convert to
+        #[arg(long, value_enum)]
+        to: ConfigFormat,
+    },
+}
+
+/// The arguments of `momi new`.
+#[derive(Args, Debug, Default)]
+pub struct NewArgs {
     /// The filename of the new document
     pub filename: String,
     /// Overwrite the file if it already exists
     #[arg(short, long, default_value_t = false)]
     pub overwrite: bool,
-    #[arg(short, long, default_value_t = false)]
-    pub verbose: bool,
     /// The author of the document
     #[arg(short, long)]
     pub author: Option<String>,
@@ -20,17 +136,252 @@ pub struct Cli {
     /// Add additional metadata to the document
     #[arg(short, long, default_value_t = false)]
     pub enrich: bool,
-    /// Create a default configuration file
-    #[arg(long)]
-    pub create_config: bool,
+    /// The value of an extra metadata. Can be repeated
+    #[arg(short, long = "field", value_name = "KEY=VALUE", value_parser = parse_field)]
+    pub fields: Vec<(String, String)>,
+    /// The name of the template to use
+    #[arg(short, long)]
+    pub template: Option<String>,
+    /// The format of the front matter that holds the metadata of the document
+    #[arg(long, value_enum)]
+    pub front_matter: Option<FrontMatter>,
+    /// Save the author, extension and front matter given here as the defaults in the config file
+    #[arg(long, default_value_t = false)]
+    pub remember: bool,
+    /// The directory to write the document to, which can have placeholders such as `minutes/{{year}}`
+    #[arg(long, value_name = "DIR")]
+    pub output_dir: Option<String>,
+    /// Print the document to stdout instead of writing it to a file
+    #[arg(long, default_value_t = false, conflicts_with = "dry_run")]
+    pub stdout: bool,
+    /// Print the path and the content of the document without writing anything
+    #[arg(long, default_value_t = false)]
+    pub dry_run: bool,
+    /// Write the document in an export format, such as docx, instead of the format of its extension
+    #[arg(long, value_enum)]
+    pub format: Option<ExportFormat>,
 }
 
-impl Cli {
-    pub fn get_extension_if_exists(&self) -> Option<String> {
+/// Parse a `key=value` pair of the `--field` option.
+fn parse_field(field: &str) -> Result<(String, String), String> {
+    match field.split_once('=') {
+        Some((key, value)) if !key.trim().is_empty() => {
+            Ok((key.trim().to_string(), value.trim().to_string()))
+        }
+        _ => Err(format!("invalid field \"{}\", expected KEY=VALUE", field)),
+    }
+}
+
+impl NewArgs {
+    /// Check that the filename has a name to use as the title of the document, unlike `..` or `/`.
+    pub fn validate_filename(&self) -> momi::Result<()> {
+        match Path::new(&self.filename).file_stem().and_then(|stem| stem.to_str()) {
+            Some(stem) if !stem.trim().is_empty() => Ok(()),
+            _ => Err(momi::Error::InvalidFilename(self.filename.clone())),
+        }
+    }
+
+    /// The values of the new document given on the command line,
+    /// with the filename split into the title and the extension.
+    pub fn to_options(&self) -> NewOptions {
         let filename = Path::new(&self.filename);
-        match filename.extension() {
-            Some(ext) => Some(ext.to_str().unwrap().to_string()),
-            None => None,
+        let part = |part: Option<&std::ffi::OsStr>| part.and_then(|part| part.to_str()).map(str::to_string);
+        NewOptions {
+            title: part(filename.file_stem()).unwrap_or_default(),
+            extension: part(filename.extension()),
+            author: self.author.clone(),
+            enrich: self.enrich,
+            fields: self.fields.clone(),
+            template: self.template.clone(),
+            front_matter: self.front_matter,
+            output_dir: self.output_dir.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::CommandFactory;
+
+    #[test]
+    fn test_validate_filename() {
+        let args = |filename: &str| NewArgs { filename: filename.to_string(), ..Default::default() };
+        assert!(args("Weekly.md").validate_filename().is_ok());
+        assert!(args("..").validate_filename().is_err());
+        assert!(args("/").validate_filename().is_err());
+        assert!(args("").validate_filename().is_err());
+    }
+
+    #[test]
+    fn test_to_options() {
+        let args = NewArgs {
+            filename: "notes/Weekly sync.md".to_string(),
+            author: Some("John Doe".to_string()),
+            ..Default::default()
+        };
+        let options = args.to_options();
+        assert_eq!(options.title, "Weekly sync");
+        assert_eq!(options.extension.as_deref(), Some("md"));
+        assert_eq!(options.author.as_deref(), Some("John Doe"));
+        let args = NewArgs { filename: "Weekly sync".to_string(), ..Default::default() };
+        assert_eq!(args.to_options().extension, None);
+    }
+
+    #[test]
+    fn test_parse_field() {
+        assert_eq!(
+            parse_field("location=Seoul").unwrap(),
+            ("location".to_string(), "Seoul".to_string())
+        );
+        assert_eq!(
+            parse_field("meeting chair = a=b").unwrap(),
+            ("meeting chair".to_string(), "a=b".to_string())
+        );
+        assert_eq!(parse_field("agenda=").unwrap(), ("agenda".to_string(), "".to_string()));
+        assert!(parse_field("location").is_err());
+        assert!(parse_field("=Seoul").is_err());
+    }
+
+    #[test]
+    fn test_fields_are_repeatable() {
+        let cli = Cli::parse_from(["momi", "-f", "location=Seoul", "--field", "agenda=Budget", "test"]);
+        assert_eq!(
+            cli.new.unwrap().fields,
+            vec![
+                ("location".to_string(), "Seoul".to_string()),
+                ("agenda".to_string(), "Budget".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_filename_without_subcommand() {
+        let cli = Cli::parse_from(["momi", "-a", "John Doe", "Meeting with the client 1.md"]);
+        assert!(cli.command.is_none());
+        let args = cli.new.unwrap();
+        assert_eq!(args.filename, "Meeting with the client 1.md");
+        assert_eq!(args.author.as_deref(), Some("John Doe"));
+    }
+
+    #[test]
+    fn test_new_subcommand() {
+        let cli = Cli::parse_from(["momi", "new", "-v", "-e", "test"]);
+        match cli.command {
+            Some(Command::New(args)) => {
+                assert_eq!(args.filename, "test");
+                assert!(args.enrich);
+            }
+            command => panic!("Unexpected command {:?}", command),
         }
+        assert!(cli.verbose);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_subcommands_without_filename() {
+        assert!(matches!(
+            Cli::parse_from(["momi", "list"]).command,
+            Some(Command::List { dir: None })
+        ));
+        assert!(matches!(
+            Cli::parse_from(["momi", "show", "test.md"]).command,
+            Some(Command::Show { .. })
+        ));
+        assert!(matches!(
+            Cli::parse_from(["momi", "config", "create"]).command,
+            Some(Command::Config(ConfigCommand::Create))
+        ));
+        assert!(matches!(
+            Cli::parse_from(["momi", "config", "migrate"]).command,
+            Some(Command::Config(ConfigCommand::Migrate))
+        ));
+    }
+
+    #[test]
+    fn test_create_config_alias() {
+        let cli = Cli::parse_from(["momi", "--create-config"]);
+        assert!(cli.create_config);
+        assert!(cli.command.is_none());
+        assert!(cli.new.is_none());
+        assert!(!Cli::command().render_help().to_string().contains("--create-config"));
+    }
+
+    #[test]
+    fn test_config_set() {
+        let cli = Cli::parse_from(["momi", "config", "set", "author", "John Doe"]);
+        match cli.command {
+            Some(Command::Config(ConfigCommand::Set { key, value })) => {
+                assert_eq!(key, "author");
+                assert_eq!(value, "John Doe");
+            }
+            _ => panic!("Expected config set"),
+        }
+        assert!(Cli::try_parse_from(["momi", "config", "set", "author"]).is_err());
+    }
+
+    #[test]
+    fn test_output_options() {
+        let args = Cli::parse_from(["momi", "test", "--stdout"]).new.unwrap();
+        assert!(args.stdout && !args.dry_run);
+        match Cli::parse_from(["momi", "new", "test", "--dry-run"]).command {
+            Some(Command::New(args)) => assert!(args.dry_run && !args.stdout),
+            command => panic!("Unexpected command {:?}", command),
+        }
+        assert!(Cli::try_parse_from(["momi", "test", "--stdout", "--dry-run"]).is_err());
+        match Cli::parse_from(["momi", "new", "test", "--format", "docx"]).command {
+            Some(Command::New(args)) => assert_eq!(args.format, Some(ExportFormat::Docx)),
+            command => panic!("Unexpected command {:?}", command),
+        }
+        assert_eq!(Cli::parse_from(["momi", "test"]).new.unwrap().format, None);
+    }
+
+    #[test]
+    fn test_export() {
+        match Cli::parse_from(["momi", "export", "--format", "html", "weekly.md"]).command {
+            Some(Command::Export { files, format, output, .. }) => {
+                assert_eq!(files, [PathBuf::from("weekly.md")]);
+                assert_eq!(format, ExportFormat::Html);
+                assert_eq!(output, None);
+            }
+            command => panic!("Unexpected command {:?}", command),
+        }
+        assert!(Cli::try_parse_from(["momi", "export", "weekly.md"]).is_err());
+        assert!(Cli::try_parse_from(["momi", "export", "--format", "html", "--stdout", "--output", "a.html", "weekly.md"]).is_err());
+        assert!(Cli::try_parse_from(["momi", "export", "--format", "ndjson"]).is_err());
+        match Cli::parse_from(["momi", "export", "--format", "ndjson", "a.md", "b.md"]).command {
+            Some(Command::Export { files, format, .. }) => {
+                assert_eq!(files, [PathBuf::from("a.md"), PathBuf::from("b.md")]);
+                assert_eq!(format, ExportFormat::Ndjson);
+            }
+            command => panic!("Unexpected command {:?}", command),
+        }
+    }
+
+    #[test]
+    fn test_config_option() {
+        let cli = Cli::parse_from(["momi", "config", "get", "author", "--config", "team.json"]);
+        assert_eq!(cli.config, Some(PathBuf::from("team.json")));
+        let cli = Cli::parse_from(["momi", "--config", "team.json", "test"]);
+        assert_eq!(cli.config, Some(PathBuf::from("team.json")));
+        assert_eq!(cli.new.unwrap().filename, "test");
+    }
+
+    #[test]
+    fn test_profile_option() {
+        let cli = Cli::parse_from(["momi", "--profile", "client", "test"]);
+        assert_eq!(cli.profile.as_deref(), Some("client"));
+        let cli = Cli::parse_from(["momi", "config", "list", "--profile", "client"]);
+        assert_eq!(cli.profile.as_deref(), Some("client"));
+        assert!(matches!(
+            Cli::parse_from(["momi", "config", "profiles"]).command,
+            Some(Command::Config(ConfigCommand::Profiles))
+        ));
+    }
+
+    #[test]
+    fn test_missing_filename() {
+        assert!(Cli::try_parse_from(["momi"]).is_err());
+        assert!(Cli::try_parse_from(["momi", "-a", "John Doe"]).is_err());
+        assert!(Cli::try_parse_from(["momi", "new"]).is_err());
+    }
+}
@@ -0,0 +1,114 @@
+//! The formats of the config files.
+//!
+//! A config file is read and written in the format given by its extension:
+//! `.toml`, `.yaml` or `.yml`, and `.json`.
+//! If a directory has config files in several formats, the one that comes first in [`ConfigFormat::PRIORITY`] is used.
+//!
+
+use std::path::{Path, PathBuf};
+use clap::ValueEnum;
+use log::warn;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use crate::error::{Error, Result};
+
+/// The format of a config file.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ConfigFormat {
+    #[default]
+    Json,
+    Toml,
+    Yaml,
+}
+
+impl ConfigFormat {
+    /// The order in which the formats are looked up when a directory has config files in several formats.
+    pub const PRIORITY: [ConfigFormat; 3] = [ConfigFormat::Toml, ConfigFormat::Yaml, ConfigFormat::Json];
+
+    /// The format of the given config file by its extension. A file with any other extension is JSON.
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Self {
+        match path.as_ref().extension().and_then(|ext| ext.to_str()) {
+            Some("toml") => ConfigFormat::Toml,
+            Some("yaml") | Some("yml") => ConfigFormat::Yaml,
+            _ => ConfigFormat::Json,
+        }
+    }
+
+    /// The extensions of the format, the preferred one first.
+    pub fn extensions(&self) -> &'static [&'static str] {
+        match self {
+            ConfigFormat::Json => &["json"],
+            ConfigFormat::Toml => &["toml"],
+            ConfigFormat::Yaml => &["yaml", "yml"],
+        }
+    }
+
+    /// Serialize the given value in this format.
+    pub fn serialize<T: Serialize>(&self, value: &T) -> Result<String> {
+        match self {
+            ConfigFormat::Json => serde_json::to_string_pretty(value).map_err(|e| e.to_string()),
+            ConfigFormat::Toml => toml::to_string_pretty(value).map_err(|e| e.to_string()),
+            ConfigFormat::Yaml => serde_yaml::to_string(value).map_err(|e| e.to_string()),
+        }
+        .map_err(Error::ConfigParse)
+    }
+
+    /// Parse the given text in this format.
+    pub fn parse<T: DeserializeOwned>(&self, text: &str) -> Result<T> {
+        match self {
+            ConfigFormat::Json => serde_json::from_str(text).map_err(|e| e.to_string()),
+            ConfigFormat::Toml => toml::from_str(text).map_err(|e| e.to_string()),
+            ConfigFormat::Yaml => serde_yaml::from_str(text).map_err(|e| e.to_string()),
+        }
+        .map_err(Error::ConfigParse)
+    }
+}
+
+/// Find the config file with the given stem in the given directory, such as `config.toml` for `config`.
+///
+/// If there are files in several formats, the one with the highest priority is used and the others are ignored.
+pub fn find_config_file<P: AsRef<Path>>(dir: P, stem: &str) -> Option<PathBuf> {
+    let mut found = ConfigFormat::PRIORITY
+        .iter()
+        .flat_map(|format| format.extensions())
+        .map(|ext| dir.as_ref().join(format!("{}.{}", stem, ext)))
+        .filter(|path| path.is_file());
+    let path = found.next()?;
+    for ignored in found {
+        warn!("{} is ignored because {} exists", ignored.display(), path.display());
+    }
+    Some(path)
+}
+
+/// The config file with the given stem in the given directory, or the JSON file if there is none.
+pub fn config_file_in<P: AsRef<Path>>(dir: P, stem: &str) -> PathBuf {
+    find_config_file(&dir, stem).unwrap_or_else(|| dir.as_ref().join(format!("{}.json", stem)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_from_path() {
+        assert_eq!(ConfigFormat::from_path("config.toml"), ConfigFormat::Toml);
+        assert_eq!(ConfigFormat::from_path("config.yml"), ConfigFormat::Yaml);
+        assert_eq!(ConfigFormat::from_path("config.yaml"), ConfigFormat::Yaml);
+        assert_eq!(ConfigFormat::from_path("config.json"), ConfigFormat::Json);
+        assert_eq!(ConfigFormat::from_path("config"), ConfigFormat::Json);
+    }
+
+    #[test]
+    fn test_find_config_file_priority() {
+        let dir = tempdir().unwrap();
+        assert_eq!(find_config_file(dir.path(), "config"), None);
+        assert_eq!(config_file_in(dir.path(), "config"), dir.path().join("config.json"));
+        fs::write(dir.path().join("config.json"), "{}").unwrap();
+        fs::write(dir.path().join("config.yml"), "").unwrap();
+        assert_eq!(config_file_in(dir.path(), "config"), dir.path().join("config.yml"));
+        fs::write(dir.path().join("config.toml"), "").unwrap();
+        assert_eq!(config_file_in(dir.path(), "config"), dir.path().join("config.toml"));
+    }
+}
@@ -0,0 +1,32 @@
+//! Front matter output mode of the document.
+//!
+
+use clap::ValueEnum;
+use serde_derive::{Deserialize, Serialize};
+
+/// The format of the front matter block that holds the metadata of the document.
+///
+/// Front matter is meant for Markdown documents that are read by static site generators
+/// or note tools such as Hugo and Obsidian.
+#[derive(Debug, Default, PartialEq, PartialOrd, Serialize, Deserialize, Clone, Copy, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum FrontMatter {
+    /// Write the metadata as `key: value` lines.
+    #[default]
+    None,
+    /// Write the metadata as a YAML block between `---` lines.
+    Yaml,
+    /// Write the metadata as a TOML block between `+++` lines.
+    Toml,
+}
+
+impl FrontMatter {
+    /// The line that opens and closes the front matter block.
+    pub fn delimiter(&self) -> Option<&'static str> {
+        match self {
+            FrontMatter::None => None,
+            FrontMatter::Yaml => Some("---"),
+            FrontMatter::Toml => Some("+++"),
+        }
+    }
+}
@@ -0,0 +1,390 @@
+//! Access to the settings of the [`Config`] by their keys in the config file.
+//!
+//! Text settings such as `author` are read and written as they are,
+//! and structured settings such as `sections`, `rich` and `profiles` are read and written as JSON.
+//!
+
+use serde::Serialize;
+use crate::config::Config;
+use crate::config::format::ConfigFormat;
+use crate::config::migration;
+use crate::config::front_matter::FrontMatter;
+use crate::config::rich_metadata::{FieldType, RESERVED_LABELS};
+use crate::error::{Error, Result};
+
+/// The keys of the settings in the config file.
+pub const KEYS: [&str; 12] = [
+    "version",
+    "author",
+    "header",
+    "footer",
+    "extension",
+    "default_template",
+    "front_matter",
+    "output_dir",
+    "filename_pattern",
+    "rich",
+    "sections",
+    "profiles",
+];
+
+/// Check that the given key is a setting of the config file.
+///
+/// The error message suggests the most similar key and lists the available keys.
+pub fn check_key(key: &str) -> Result<()> {
+    if KEYS.contains(&key) {
+        return Ok(());
+    }
+    let suggestion = KEYS
+        .iter()
+        .map(|candidate| (distance(key, candidate), candidate))
+        .filter(|(distance, _)| *distance <= 3)
+        .min();
+    Err(Error::InvalidSetting(match suggestion {
+        Some((_, candidate)) => format!(
+            "Unknown key \"{}\". Did you mean \"{}\"? Available keys: {}",
+            key,
+            candidate,
+            KEYS.join(", ")
+        ),
+        None => format!("Unknown key \"{}\". Available keys: {}", key, KEYS.join(", ")),
+    }))
+}
+
+/// The edit distance between the given strings.
+fn distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, a) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, b) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(a != *b);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+/// Validate the content of a config file in the given format and return the problems found in it.
+///
+/// Besides the types of the settings, the following are checked:
+/// - the version is supported by this version of momi
+/// - every key is a setting of the config file, and every key of a profile is a setting a profile can have
+/// - every section has a heading
+/// - no extra metadata field has the label of a standard metadata, such as `title`
+/// - every enum extra metadata field has its `values`
+/// - the default value of every extra metadata field is valid for its type
+/// - the `default_template` is one of the given templates
+pub fn validate(config_text: &str, format: ConfigFormat, templates: &[String]) -> Vec<String> {
+    let value: serde_json::Value = match format.parse(config_text) {
+        Ok(value) => value,
+        Err(e) => return vec![format!("Error parsing the config file: {}", e)],
+    };
+    let Some(object) = value.as_object() else {
+        return vec!["The config file must be a map of settings".to_string()];
+    };
+    if let Err(e) = migration::migrate(&mut value.clone()) {
+        return vec![e.to_string()];
+    }
+    let mut problems: Vec<String> = object
+        .keys()
+        .filter_map(|key| check_key(key).err().map(|e| e.to_string()))
+        .collect();
+    for (name, profile) in object.get("profiles").and_then(|profiles| profiles.as_object()).into_iter().flatten() {
+        for key in profile.as_object().into_iter().flat_map(|profile| profile.keys()) {
+            match key.as_str() {
+                "version" | "profiles" => problems.push(format!("Profile \"{}\": \"{}\" cannot be set in a profile", name, key)),
+                key => problems.extend(check_key(key).err().map(|e| format!("Profile \"{}\": {}", name, e))),
+            }
+        }
+    }
+    for (key, value) in object {
+        if !KEYS.contains(&key.as_str()) {
+            continue;
+        }
+        let mut single = serde_json::Map::new();
+        single.insert(key.clone(), value.clone());
+        if let Err(e) = serde_json::from_value::<Config>(serde_json::Value::Object(single)) {
+            problems.push(format!("Invalid {}: {}", key, e));
+        }
+    }
+    if !problems.is_empty() {
+        return problems;
+    }
+
+    let config: Config = match serde_json::from_value(value) {
+        Ok(config) => config,
+        Err(e) => return vec![e.to_string()],
+    };
+    for section in config.settings.sections.iter().flatten() {
+        if section.heading.trim().is_empty() {
+            problems.push("A section has an empty heading".to_string());
+        }
+    }
+    for field in config.settings.rich.iter().flat_map(|rich| &rich.extra_metadata) {
+        if RESERVED_LABELS.contains(&field.label.as_str()) {
+            problems.push(format!("The extra metadata field \"{}\" has the label of a standard metadata", field.label));
+        }
+        if field.field_type == FieldType::Enum && field.values.as_ref().is_none_or(|values| values.is_empty()) {
+            problems.push(format!("The enum field \"{}\" has no values", field.label));
+        }
+        if let Some(default) = &field.default {
+            if let Err(e) = field.validate(default) {
+                problems.push(format!("Invalid default of \"{}\": {}", field.label, e));
+            }
+        }
+    }
+    if let Some(name) = &config.settings.default_template {
+        if !templates.contains(name) {
+            problems.push(format!(
+                "Unknown default_template \"{}\". Available templates: {}",
+                name,
+                templates.join(", ")
+            ));
+        }
+    }
+    problems
+}
+
+/// Write the given structured setting as JSON.
+fn to_json<T: Serialize>(value: &T) -> Result<String> {
+    serde_json::to_string(value).map_err(|e| Error::ConfigParse(e.to_string()))
+}
+
+impl Config {
+    /// Get the value of the setting with the given key, or `None` if it is not set.
+    pub fn get(&self, key: &str) -> Result<Option<String>> {
+        check_key(key)?;
+        Ok(match key {
+            "version" => Some(self.version.to_string()),
+            "author" => self.settings.author.clone(),
+            "header" => self.settings.header.clone(),
+            "footer" => self.settings.footer.clone(),
+            "extension" => self.settings.extension.clone(),
+            "default_template" => self.settings.default_template.clone(),
+            "output_dir" => self.settings.output_dir.clone(),
+            "filename_pattern" => self.settings.filename_pattern.clone(),
+            "front_matter" => self.settings.front_matter.map(|front_matter| {
+                serde_json::to_value(front_matter)
+                    .ok()
+                    .and_then(|value| value.as_str().map(|value| value.to_string()))
+                    .unwrap_or_default()
+            }),
+            "rich" => match &self.settings.rich {
+                Some(rich) => Some(to_json(rich)?),
+                None => None,
+            },
+            "sections" => match &self.settings.sections {
+                Some(sections) => Some(to_json(sections)?),
+                None => None,
+            },
+            "profiles" => match &self.profiles {
+                Some(profiles) => Some(to_json(profiles)?),
+                None => None,
+            },
+            _ => None,
+        })
+    }
+
+    /// Set the setting with the given key to the given value.
+    ///
+    /// The value is checked against the type of the setting before it is set.
+    pub fn set(&mut self, key: &str, value: &str) -> Result<()> {
+        check_key(key)?;
+        match key {
+            "version" => return Err(Error::InvalidSetting(
+                "The version is set by momi when the config file is migrated".to_string(),
+            )),
+            "author" => self.set_author(value.to_string()),
+            "header" => self.set_header(Some(value.to_string())),
+            "footer" => self.set_footer(Some(value.to_string())),
+            "extension" => self.set_extension(Some(value.trim_start_matches('.').to_string())),
+            "default_template" => self.set_default_template(Some(value.to_string())),
+            "output_dir" => self.set_output_dir(Some(value.to_string())),
+            "filename_pattern" => self.set_filename_pattern(Some(value.to_string())),
+            "front_matter" => {
+                let front_matter: FrontMatter = serde_json::from_value(serde_json::Value::String(value.to_string()))
+                    .map_err(|_| {
+                        Error::InvalidSetting(format!(
+                            "Invalid front_matter \"{}\". Expected one of none, yaml, toml",
+                            value
+                        ))
+                    })?;
+                self.set_front_matter(Some(front_matter));
+            }
+            "rich" => self.set_rich(
+                serde_json::from_str(value)
+                    .map_err(|e| Error::InvalidSetting(format!("Invalid rich \"{}\": {}", value, e)))?,
+            ),
+            "sections" => self.set_sections(Some(
+                serde_json::from_str(value)
+                    .map_err(|e| Error::InvalidSetting(format!("Invalid sections \"{}\": {}", value, e)))?,
+            )),
+            "profiles" => self.profiles = Some(
+                serde_json::from_str(value)
+                    .map_err(|e| Error::InvalidSetting(format!("Invalid profiles \"{}\": {}", value, e)))?,
+            ),
+            _ => (),
+        }
+        Ok(())
+    }
+
+    /// Remove the setting with the given key, so its default value is used.
+    pub fn unset(&mut self, key: &str) -> Result<()> {
+        check_key(key)?;
+        match key {
+            "version" => return Err(Error::InvalidSetting(
+                "The version is set by momi when the config file is migrated".to_string(),
+            )),
+            "author" => self.settings.author = None,
+            "header" => self.set_header(None),
+            "footer" => self.set_footer(None),
+            "extension" => self.set_extension(None),
+            "default_template" => self.set_default_template(None),
+            "output_dir" => self.set_output_dir(None),
+            "filename_pattern" => self.set_filename_pattern(None),
+            "front_matter" => self.set_front_matter(None),
+            "rich" => self.settings.rich = None,
+            "sections" => self.set_sections(None),
+            "profiles" => self.profiles = None,
+            _ => (),
+        }
+        Ok(())
+    }
+
+    /// List the keys and values of the settings that are set.
+    pub fn list(&self) -> Result<Vec<(&'static str, String)>> {
+        let mut settings = vec![];
+        for key in KEYS {
+            if let Some(value) = self.get(key)? {
+                settings.push((key, value));
+            }
+        }
+        Ok(settings)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::migration::CONFIG_VERSION;
+    use crate::config::section::Section;
+
+    #[test]
+    fn test_check_key_with_typo() {
+        assert!(check_key("author").is_ok());
+        let error = check_key("autor").unwrap_err();
+        assert!(error.to_string().starts_with("Unknown key \"autor\". Did you mean \"author\"?"));
+        let error = check_key("colour").unwrap_err();
+        assert!(error.to_string().starts_with("Unknown key \"colour\". Available keys: version, author"));
+    }
+
+    #[test]
+    fn test_set_and_get() {
+        let mut config = Config::new();
+        config.set("author", "John Doe").unwrap();
+        config.set("extension", ".md").unwrap();
+        config.set("front_matter", "yaml").unwrap();
+        assert_eq!(config.get("author").unwrap().as_deref(), Some("John Doe"));
+        assert_eq!(config.get("extension").unwrap().as_deref(), Some("md"));
+        assert_eq!(config.get("front_matter").unwrap().as_deref(), Some("yaml"));
+        assert_eq!(config.get("header").unwrap(), None);
+    }
+
+    #[test]
+    fn test_set_structured_values() {
+        let mut config = Config::new();
+        config.set("sections", r#"[{"heading": "Action Items"}]"#).unwrap();
+        assert_eq!(config.settings.sections.clone().unwrap(), vec![Section::new("Action Items")]);
+        assert_eq!(
+            config.get("sections").unwrap().as_deref(),
+            Some(r#"[{"heading":"Action Items","body":null,"order":null}]"#)
+        );
+        config.set("rich", r#"{"extra_metadata": ["location"]}"#).unwrap();
+        assert_eq!(config.settings.rich.unwrap().extra_metadata, vec!["location".into()]);
+    }
+
+    #[test]
+    fn test_set_invalid_values() {
+        let mut config = Config::new();
+        assert!(config.set("front_matter", "json").is_err());
+        assert!(config.set("sections", "Action Items").is_err());
+        assert!(config.set("autor", "John Doe").is_err());
+        assert!(config.set("version", "1").is_err());
+    }
+
+    #[test]
+    fn test_unset() {
+        let mut config = Config::new();
+        config.set("author", "John Doe").unwrap();
+        config.unset("author").unwrap();
+        assert_eq!(config.get("author").unwrap(), None);
+    }
+
+    #[test]
+    fn test_validate() {
+        let templates = vec!["standup".to_string()];
+        let json = ConfigFormat::Json;
+        assert!(validate(r#"{"author": "John Doe", "default_template": "standup"}"#, json, &templates).is_empty());
+        assert_eq!(
+            validate(r#"{"autor": "John Doe", "front_matter": "json"}"#, json, &templates).len(),
+            2
+        );
+        assert_eq!(validate("[]", json, &templates), vec!["The config file must be a map of settings"]);
+        assert!(validate("{", json, &templates)[0].starts_with("Error parsing the config file"));
+        assert!(validate("author = \"John Doe\"\n", ConfigFormat::Toml, &templates).is_empty());
+        assert!(validate(r#"{"version": 99}"#, json, &templates)[0].contains("Please update momi"));
+        assert_eq!(validate("autor: John Doe\n", ConfigFormat::Yaml, &templates).len(), 1);
+    }
+
+    #[test]
+    fn test_validate_profile_keys() {
+        let problems = validate(
+            r#"{"profiles": {"client": {"autor": "Jane Doe", "version": 2}, "internal": {"author": "John Doe"}}}"#,
+            ConfigFormat::Json,
+            &[],
+        );
+        assert_eq!(problems.len(), 2);
+        assert!(problems[0].starts_with("Profile \"client\": Unknown key \"autor\". Did you mean \"author\"?"));
+        assert_eq!(problems[1], "Profile \"client\": \"version\" cannot be set in a profile");
+    }
+
+    #[test]
+    fn test_validate_semantics() {
+        let problems = validate(
+            r#"{
+                "sections": [{"heading": " "}],
+                "rich": {"extra_metadata": [
+                    "author",
+                    {"label": "kind", "type": "enum"},
+                    {"label": "date", "type": "date", "default": "tomorrow"}
+                ]},
+                "default_template": "retro"
+            }"#,
+            ConfigFormat::Json,
+            &["standup".to_string()],
+        );
+        assert_eq!(problems.len(), 5);
+        assert_eq!(problems[0], "A section has an empty heading");
+        assert_eq!(problems[1], "The extra metadata field \"author\" has the label of a standard metadata");
+        assert_eq!(problems[2], "The enum field \"kind\" has no values");
+        assert!(problems[3].starts_with("Invalid default of \"date\""));
+        assert_eq!(problems[4], "Unknown default_template \"retro\". Available templates: standup");
+    }
+
+    #[test]
+    fn test_list() {
+        let mut config = Config::default();
+        config.set("footer", "bye").unwrap();
+        config.set("author", "John Doe").unwrap();
+        assert_eq!(
+            config.list().unwrap(),
+            vec![
+                ("version", CONFIG_VERSION.to_string()),
+                ("author", "John Doe".to_string()),
+                ("footer", "bye".to_string()),
+            ]
+        );
+    }
+}
@@ -0,0 +1,288 @@
+//! Layered lookup of the config files.
+//!
+//! The config files are merged field by field, and a field of a later layer overrides the same field of an earlier one:
+//!
+//! Every config file can be written in TOML, YAML or JSON, as described in [`format`](super::format).
+//!
+//! 1. The system config, `/etc/momi/config.json` (`%PROGRAMDATA%\momi\config.json` on Windows)
+//! 2. The config next to the executable, `config.json`, as in earlier versions of momi
+//! 3. The user config, `$XDG_CONFIG_HOME/momi/config.json` (`~/.config/momi/config.json` if it is not set)
+//! 4. The project config, `.momi/config.json` or `.momi.json` in the current directory or the nearest parent directory that has one
+//! 5. The config given with `--config <path>` or the `MOMI_CONFIG` environment variable, which must exist
+//!
+//! The command line options override all of them.
+//!
+
+use std::env;
+use std::fmt;
+use std::io;
+use std::path::{Path, PathBuf};
+use log::info;
+use crate::config::Config;
+use crate::error::{Error, Result};
+use crate::config::format::{config_file_in, find_config_file};
+use crate::config::project::{Project, PROJECT_DIR};
+use crate::config::template::TEMPLATES_DIR;
+
+/// The stem of the config file in the system, executable and user config directories.
+pub const CONFIG_STEM: &str = "config";
+/// The name of the config file in the system, executable and user config directories if there is none.
+pub const CONFIG_FILE: &str = "config.json";
+/// The stem of the project config file.
+pub const PROJECT_CONFIG_STEM: &str = ".momi";
+/// The name of the project config file if there is none.
+pub const PROJECT_CONFIG_FILE: &str = ".momi.json";
+/// The environment variable holding the path of the explicit config file.
+pub const CONFIG_ENV: &str = "MOMI_CONFIG";
+
+/// A layer of the configuration, from the lowest to the highest precedence.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Layer {
+    System,
+    Executable,
+    User,
+    Project,
+    Explicit,
+}
+
+impl fmt::Display for Layer {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Layer::System => "system",
+            Layer::Executable => "executable",
+            Layer::User => "user",
+            Layer::Project => "project",
+            Layer::Explicit => "explicit",
+        };
+        f.pad(name)
+    }
+}
+
+/// The config files of every layer, from the lowest to the highest precedence.
+/// The files do not have to exist.
+#[derive(Debug, Default, PartialEq)]
+pub struct ConfigLayers {
+    pub layers: Vec<(Layer, PathBuf)>,
+}
+
+/// The user config directory, `$XDG_CONFIG_HOME/momi` or `~/.config/momi`.
+pub fn user_config_dir() -> Option<PathBuf> {
+    if let Some(dir) = env::var_os("XDG_CONFIG_HOME").filter(|dir| !dir.is_empty()) {
+        return Some(PathBuf::from(dir).join("momi"));
+    }
+    if let Some(home) = env::var_os("HOME").filter(|home| !home.is_empty()) {
+        return Some(PathBuf::from(home).join(".config").join("momi"));
+    }
+    env::var_os("APPDATA").map(|dir| PathBuf::from(dir).join("momi"))
+}
+
+/// The system config directory, `/etc/momi` or `%PROGRAMDATA%\momi` on Windows.
+pub fn system_config_dir() -> Option<PathBuf> {
+    match cfg!(windows) {
+        true => env::var_os("PROGRAMDATA").map(|dir| PathBuf::from(dir).join("momi")),
+        false => Some(PathBuf::from("/etc/momi")),
+    }
+}
+
+/// Find the project config in the given directory or the nearest parent directory that has one.
+///
+/// The config file of a [`Project`] is used even if it does not exist,
+/// so the templates of the project are found without a project config.
+pub fn find_project_config<P: AsRef<Path>>(dir: P) -> Option<PathBuf> {
+    dir.as_ref().ancestors().find_map(|dir| {
+        if dir.join(PROJECT_DIR).is_dir() {
+            return Some(Project { root: dir.to_path_buf() }.config_file());
+        }
+        find_config_file(dir, PROJECT_CONFIG_STEM)
+    })
+}
+
+impl ConfigLayers {
+    /// Find the config files of every layer.
+    ///
+    /// The explicit config is the given path, or the path in the `MOMI_CONFIG` environment variable.
+    pub fn discover<P: AsRef<Path>, Q: AsRef<Path>>(explicit: Option<PathBuf>, current_exe_dir: P, current_dir: Q) -> Self {
+        let mut layers = vec![];
+        if let Some(dir) = system_config_dir() {
+            layers.push((Layer::System, config_file_in(dir, CONFIG_STEM)));
+        }
+        layers.push((Layer::Executable, config_file_in(current_exe_dir, CONFIG_STEM)));
+        if let Some(dir) = user_config_dir() {
+            layers.push((Layer::User, config_file_in(dir, CONFIG_STEM)));
+        }
+        if let Some(path) = find_project_config(current_dir) {
+            layers.push((Layer::Project, path));
+        }
+        let explicit = explicit.or_else(|| env::var_os(CONFIG_ENV).filter(|path| !path.is_empty()).map(PathBuf::from));
+        if let Some(path) = explicit {
+            layers.push((Layer::Explicit, path));
+        }
+        ConfigLayers { layers }
+    }
+
+    /// Load the config files that exist and merge them in the order of the layers.
+    ///
+    /// A config file of an older version is upgraded in memory, without changing the file.
+    /// A config file that exists but cannot be read is an error, so it is never replaced by the defaults.
+    /// The explicit config file must exist, while the config files of the other layers are optional.
+    pub fn load(&self) -> Result<Config> {
+        let mut config = Config::new();
+        for (layer, path) in &self.layers {
+            if !path.is_file() {
+                if *layer == Layer::Explicit {
+                    return Err(Error::ConfigIo {
+                        path: path.clone(),
+                        source: io::Error::new(io::ErrorKind::NotFound, "The config file does not exist"),
+                    });
+                }
+                info!("No {} config file at {}", layer, path.display());
+                continue;
+            }
+            let layer_config = Config::from_file(path).map_err(|e| match e {
+                Error::ConfigParse(message) => Error::ConfigParse(format!("Error loading {} config file {}", layer, message)),
+                e => e,
+            })?;
+            info!("Loaded {} config file {}", layer, path.display());
+            config.merge(layer_config);
+        }
+        Ok(config)
+    }
+
+    /// The config file changed by `momi config`.
+    ///
+    /// It is the explicit config if one is given, and otherwise the user config.
+    pub fn target(&self) -> Option<&Path> {
+        [Layer::Explicit, Layer::User, Layer::Executable]
+            .iter()
+            .find_map(|layer| self.path(*layer))
+    }
+
+    /// The config file of the given layer.
+    pub fn path(&self, layer: Layer) -> Option<&Path> {
+        self.layers
+            .iter()
+            .find(|(l, _)| *l == layer)
+            .map(|(_, path)| path.as_path())
+    }
+
+    /// The template directories next to the config files, from the highest to the lowest precedence.
+    /// A `.momi.json` project config has no template directory, unlike a `.momi` directory.
+    pub fn templates_dirs(&self) -> Vec<PathBuf> {
+        self.layers
+            .iter()
+            .rev()
+            .filter(|(_, path)| path.file_stem().is_none_or(|stem| stem != PROJECT_CONFIG_STEM))
+            .filter_map(|(_, path)| path.parent().map(|dir| dir.join(TEMPLATES_DIR)))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_find_project_config() {
+        let dir = tempdir().unwrap();
+        let nested = dir.path().join("a").join("b");
+        fs::create_dir_all(&nested).unwrap();
+        assert_eq!(find_project_config(&nested), None);
+        fs::write(dir.path().join("a").join(PROJECT_CONFIG_FILE), "{}").unwrap();
+        assert_eq!(
+            find_project_config(&nested),
+            Some(dir.path().join("a").join(PROJECT_CONFIG_FILE))
+        );
+        fs::create_dir(dir.path().join("a").join("b").join(PROJECT_DIR)).unwrap();
+        assert_eq!(
+            find_project_config(&nested),
+            Some(nested.join(PROJECT_DIR).join(CONFIG_FILE))
+        );
+    }
+
+    #[test]
+    fn test_load_merges_layers() {
+        let dir = tempdir().unwrap();
+        let user = dir.path().join("user.json");
+        let project = dir.path().join("project.json");
+        fs::write(&user, r#"{"author": "John Doe", "extension": "md", "footer": "bye"}"#).unwrap();
+        fs::write(&project, r#"{"author": "Jane Doe", "header": "hi"}"#).unwrap();
+        let layers = ConfigLayers {
+            layers: vec![
+                (Layer::System, dir.path().join("missing.json")),
+                (Layer::User, user),
+                (Layer::Project, project),
+            ],
+        };
+        let config = layers.load().unwrap();
+        assert_eq!(config.settings.author.as_deref(), Some("Jane Doe"));
+        assert_eq!(config.settings.extension.as_deref(), Some("md"));
+        assert_eq!(config.settings.header.as_deref(), Some("hi"));
+        assert_eq!(config.settings.footer.as_deref(), Some("bye"));
+    }
+
+    #[test]
+    fn test_load_with_invalid_file() {
+        let dir = tempdir().unwrap();
+        let user = dir.path().join("user.json");
+        fs::write(&user, "{").unwrap();
+        let layers = ConfigLayers { layers: vec![(Layer::User, user.clone())] };
+        let error = layers.load().unwrap_err();
+        assert!(error.to_string().starts_with(&format!("Error loading user config file {}", user.display())));
+        assert_eq!(fs::read_to_string(&user).unwrap(), "{");
+    }
+
+    #[test]
+    fn test_load_does_not_migrate_files() {
+        let dir = tempdir().unwrap();
+        let user = dir.path().join("config.json");
+        let original = r#"{"author": "John Doe", "colour": "red", "rich": {"extra_metadata": ["location"]}}"#;
+        fs::write(&user, original).unwrap();
+        let config = ConfigLayers { layers: vec![(Layer::User, user.clone())] }.load().unwrap();
+        assert_eq!(config.settings.rich.unwrap().extra_metadata[0].label, "location");
+        assert_eq!(fs::read_to_string(&user).unwrap(), original);
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 1);
+    }
+
+    #[test]
+    fn test_load_with_missing_explicit_file() {
+        let dir = tempdir().unwrap();
+        let explicit = dir.path().join("missing.json");
+        let layers = ConfigLayers {
+            layers: vec![(Layer::User, dir.path().join("user.json")), (Layer::Explicit, explicit.clone())],
+        };
+        match layers.load() {
+            Err(Error::ConfigIo { path, source }) => {
+                assert_eq!(path, explicit);
+                assert_eq!(source.kind(), io::ErrorKind::NotFound);
+            }
+            result => panic!("Expected a ConfigIo error, got {:?}", result),
+        }
+    }
+
+    #[test]
+    fn test_target_and_templates_dirs() {
+        let layers = ConfigLayers {
+            layers: vec![
+                (Layer::Executable, PathBuf::from("/bin/config.json")),
+                (Layer::User, PathBuf::from("/home/john/.config/momi/config.json")),
+                (Layer::Project, PathBuf::from("/work/.momi.json")),
+            ],
+        };
+        let mut with_project_dir = ConfigLayers { layers: layers.layers.clone() };
+        with_project_dir.layers[2].1 = PathBuf::from("/work/.momi/config.json");
+        assert_eq!(with_project_dir.templates_dirs()[0], PathBuf::from("/work/.momi/templates"));
+        assert_eq!(layers.target(), Some(Path::new("/home/john/.config/momi/config.json")));
+        assert_eq!(
+            layers.templates_dirs(),
+            vec![
+                PathBuf::from("/home/john/.config/momi/templates"),
+                PathBuf::from("/bin/templates"),
+            ]
+        );
+        let mut layers = layers;
+        layers.layers.push((Layer::Explicit, PathBuf::from("/tmp/momi.json")));
+        assert_eq!(layers.target(), Some(Path::new("/tmp/momi.json")));
+    }
+}
@@ -0,0 +1,168 @@
+//! Versioning and migration of the config files.
+//!
+//! Every config file written by momi has a `version` key. A config file without it is version 1,
+//! the shape used before the key was added. When a config file of an older version is loaded,
+//! it is upgraded step by step to [`CONFIG_VERSION`] in memory, and the file is left as it is.
+//! The file is only rewritten by `momi config migrate`, which keeps the original as a backup,
+//! and by the commands that write the config file, such as `momi config set`.
+//!
+//! - Version 2: the plain strings in `rich.extra_metadata` are written as typed fields.
+//!
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use serde_json::{Map, Value};
+use crate::config::{backup_path, Config};
+use crate::config::format::ConfigFormat;
+use crate::error::{Error, Result};
+
+/// The version of the config files written by this version of momi.
+pub const CONFIG_VERSION: u32 = 2;
+
+/// The migrations, where the migration at index `i` upgrades version `i + 1` to `i + 2`.
+const MIGRATIONS: [fn(&mut Map<String, Value>); (CONFIG_VERSION - 1) as usize] = [typed_extra_metadata];
+
+/// Version 2: write the plain strings in `rich.extra_metadata` as typed text fields.
+fn typed_extra_metadata(config: &mut Map<String, Value>) {
+    let fields = config
+        .get_mut("rich")
+        .and_then(|rich| rich.get_mut("extra_metadata"))
+        .and_then(|fields| fields.as_array_mut());
+    for field in fields.into_iter().flatten() {
+        if let Value::String(label) = field {
+            *field = serde_json::json!({ "label": label, "type": "text" });
+        }
+    }
+}
+
+/// The version of the given config. A config without a version is version 1.
+pub fn version_of(config: &Value) -> Result<u32> {
+    match config.get("version") {
+        None | Some(Value::Null) => Ok(1),
+        Some(version) => version
+            .as_u64()
+            .and_then(|version| u32::try_from(version).ok())
+            .filter(|version| *version >= 1)
+            .ok_or_else(|| {
+                Error::ConfigParse(format!("Invalid version {}. The version must be a positive integer", version))
+            }),
+    }
+}
+
+/// Upgrade the given config to [`CONFIG_VERSION`] and return whether it was changed.
+///
+/// A config of a newer version than this version of momi supports is an error.
+pub fn migrate(config: &mut Value) -> Result<bool> {
+    let version = version_of(config)?;
+    if version > CONFIG_VERSION {
+        return Err(Error::ConfigParse(format!(
+            "The config file is version {}, but this version of momi supports up to version {}. Please update momi",
+            version, CONFIG_VERSION
+        )));
+    }
+    if version == CONFIG_VERSION {
+        return Ok(false);
+    }
+    let Some(map) = config.as_object_mut() else {
+        return Err(Error::ConfigParse("The config file must be a map of settings".to_string()));
+    };
+    for migration in &MIGRATIONS[(version - 1) as usize..] {
+        migration(map);
+    }
+    map.insert("version".to_string(), Value::from(CONFIG_VERSION));
+    Ok(true)
+}
+
+/// Upgrade the given config file to [`CONFIG_VERSION`] if it is of an older version.
+///
+/// The original file is kept as a backup with `.v<version>.bak` appended to its name, see [`backup_path`],
+/// and the path of the backup is returned. If the file is up to date, nothing is written.
+pub fn migrate_file<P: AsRef<Path>>(config_file: P) -> Result<Option<PathBuf>> {
+    let config_file = config_file.as_ref();
+    let config_io = |source| Error::ConfigIo { path: config_file.to_path_buf(), source };
+    let format = ConfigFormat::from_path(config_file);
+    let text = fs::read_to_string(config_file).map_err(config_io)?;
+    let mut value: Value = format.parse(&text)?;
+    let version = version_of(&value)?;
+    if !migrate(&mut value)? {
+        return Ok(None);
+    }
+    let config: Config = serde_json::from_value(value).map_err(|e| Error::ConfigParse(e.to_string()))?;
+    let backup = backup_path(config_file, &format!(".v{}", version));
+    fs::copy(config_file, &backup).map_err(config_io)?;
+    config.create_config_file(config_file)?;
+    Ok(Some(backup))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::rich_metadata::{ExtraField, FieldType};
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_version_of() {
+        assert_eq!(version_of(&serde_json::json!({})).unwrap(), 1);
+        assert_eq!(version_of(&serde_json::json!({"version": 2})).unwrap(), 2);
+        assert!(version_of(&serde_json::json!({"version": "two"})).is_err());
+        assert!(version_of(&serde_json::json!({"version": 0})).is_err());
+    }
+
+    #[test]
+    fn test_migrate_from_version_1() {
+        let mut config = serde_json::json!({
+            "author": "John Doe",
+            "rich": {"extra_metadata": ["location", {"label": "date", "type": "date"}]}
+        });
+        assert!(migrate(&mut config).unwrap());
+        assert_eq!(
+            config,
+            serde_json::json!({
+                "version": CONFIG_VERSION,
+                "author": "John Doe",
+                "rich": {"extra_metadata": [
+                    {"label": "location", "type": "text"},
+                    {"label": "date", "type": "date"}
+                ]}
+            })
+        );
+        assert!(!migrate(&mut config).unwrap());
+    }
+
+    #[test]
+    fn test_migrate_newer_version() {
+        let mut config = serde_json::json!({"version": CONFIG_VERSION + 1});
+        let error = migrate(&mut config).unwrap_err();
+        assert!(error.to_string().contains("Please update momi"));
+    }
+
+    #[test]
+    fn test_migrate_file() {
+        let dir = tempdir().unwrap();
+        let config_file = dir.path().join("config.json");
+        let original = r#"{"author": "John Doe", "rich": {"extra_metadata": ["location"]}}"#;
+        fs::write(&config_file, original).unwrap();
+        let backup = migrate_file(&config_file).unwrap().unwrap();
+        assert_eq!(backup, dir.path().join("config.json.v1.bak"));
+        assert_eq!(fs::read_to_string(&backup).unwrap(), original);
+        let config = Config::from_file(&config_file).unwrap();
+        assert_eq!(config.version, CONFIG_VERSION);
+        assert_eq!(config.settings.author.as_deref(), Some("John Doe"));
+        assert_eq!(
+            config.settings.rich.unwrap().extra_metadata,
+            vec![ExtraField { field_type: FieldType::Text, ..ExtraField::from("location") }]
+        );
+        assert_eq!(migrate_file(&config_file).unwrap(), None);
+    }
+
+    #[test]
+    fn test_migrate_file_keeps_existing_backup() {
+        let dir = tempdir().unwrap();
+        let config_file = dir.path().join("config.json");
+        fs::write(&config_file, r#"{"author": "John Doe"}"#).unwrap();
+        fs::write(dir.path().join("config.json.v1.bak"), "older backup").unwrap();
+        let backup = migrate_file(&config_file).unwrap().unwrap();
+        assert_eq!(backup, dir.path().join("config.json.v1.2.bak"));
+        assert_eq!(fs::read_to_string(dir.path().join("config.json.v1.bak")).unwrap(), "older backup");
+    }
+}
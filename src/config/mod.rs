@@ -8,65 +8,200 @@
 
 use serde::Serialize;
 use serde_derive::Deserialize;
-use std::error::Error;
+use std::collections::BTreeMap;
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use crate::error::{Error, Result};
+use format::ConfigFormat;
+use front_matter::FrontMatter;
+use migration::CONFIG_VERSION;
+use profile::Profile;
 use rich_metadata::RichMetadata;
+use section::Section;
+use settings::Settings;
 
+pub mod format;
+pub mod front_matter;
+pub mod keys;
+pub mod layers;
+pub mod migration;
+pub mod profile;
+pub mod project;
 pub mod rich_metadata;
+pub mod section;
+pub mod settings;
+pub mod template;
 
-#[derive(Default, Serialize, Deserialize, PartialOrd, PartialEq, Debug)]
+#[derive(Serialize, Deserialize, PartialOrd, PartialEq, Debug)]
 pub struct Config {
-    /// The author of the document.
-    pub author: Option<String>,
-    /// The header of the document.
-    pub header: Option<String>,
-    /// The footer of the document.
-    pub footer: Option<String>,
-    /// The extension of the document.
-    pub extension: Option<String>,
-    pub rich: Option<RichMetadata>,
+    /// The version of the config file. See [`migration`].
+    #[serde(default = "current_version")]
+    pub version: u32,
+    /// The settings of the document, which the profiles and the templates override.
+    #[serde(flatten)]
+    pub settings: Settings,
+    /// The named profiles that override the other fields when they are selected.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub profiles: Option<BTreeMap<String, Profile>>,
+}
+
+fn current_version() -> u32 {
+    CONFIG_VERSION
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            version: CONFIG_VERSION,
+            settings: Settings::default(),
+            profiles: None,
+        }
+    }
 }
 
 impl Config {
     pub fn new() -> Self {
-        let mut config = Config::default();
-        config.rich = Some(RichMetadata::default());
-        config
+        Config {
+            settings: Settings { rich: Some(RichMetadata::default()), ..Default::default() },
+            ..Default::default()
+        }
     }
 
     pub fn set_author(&mut self, author: String) {
-        self.author = Some(author);
+        self.settings.author = Some(author);
     }
 
     pub fn set_header(&mut self, header: Option<String>) {
-        self.header = header;
+        self.settings.header = header;
     }
 
     pub fn set_footer(&mut self, footer: Option<String>) {
-        self.footer = Option::from(footer);
+        self.settings.footer = footer;
     }
 
     pub fn set_extension(&mut self, extension: Option<String>) {
-        self.extension = extension;
+        self.settings.extension = extension;
     }
 
 
     pub fn set_rich(&mut self, rich: RichMetadata) {
-        self.rich = Some(rich);
+        self.settings.rich = Some(rich);
+    }
+
+    pub fn set_sections(&mut self, sections: Option<Vec<Section>>) {
+        self.settings.sections = sections;
+    }
+
+    pub fn set_default_template(&mut self, default_template: Option<String>) {
+        self.settings.default_template = default_template;
+    }
+
+    pub fn set_front_matter(&mut self, front_matter: Option<FrontMatter>) {
+        self.settings.front_matter = front_matter;
+    }
+
+    pub fn set_output_dir(&mut self, output_dir: Option<String>) {
+        self.settings.output_dir = output_dir;
+    }
+
+    pub fn set_filename_pattern(&mut self, filename_pattern: Option<String>) {
+        self.settings.filename_pattern = filename_pattern;
+    }
+
+    /// Override the settings of this config with the settings that are set in the given config.
+    /// The version is not changed.
+    pub fn merge(&mut self, other: Config) {
+        self.settings.merge(other.settings);
+        // The profiles with the same name are merged field by field too.
+        for (name, profile) in other.profiles.into_iter().flatten() {
+            self.profiles
+                .get_or_insert_with(BTreeMap::new)
+                .entry(name)
+                .or_default()
+                .settings
+                .merge(profile.settings);
+        }
     }
 
-    pub fn create_config_file<P: AsRef<Path>>(&self, config_file: P) -> Result<(), Box<dyn Error>> {
-        let config_json = serde_json::to_string_pretty(self)?;
-        fs::write(&config_file, config_json)?;
+    /// Write the config to the given file in the format given by its extension.
+    pub fn create_config_file<P: AsRef<Path>>(&self, config_file: P) -> Result<()> {
+        let config_file = config_file.as_ref();
+        let config_json = ConfigFormat::from_path(config_file).serialize(self)?;
+        let config_io = |source| Error::ConfigIo { path: config_file.to_path_buf(), source };
+        if let Some(dir) = config_file.parent() {
+            fs::create_dir_all(dir).map_err(config_io)?;
+        }
+        fs::write(config_file, config_json).map_err(config_io)?;
         Ok(())
     }
 
-    pub fn from_file<P: AsRef<Path>>(config_file: P) -> Result<Self, Box<dyn Error>> {
-        let config_json = fs::read_to_string(&config_file)?;
-        let config: Config = serde_json::from_str(&config_json)?;
-        Ok(config)
+    /// Read the config from the given file in the format given by its extension.
+    ///
+    /// A config file of an older version is upgraded in memory, without changing the file.
+    /// Use [`migration::migrate_file`] to upgrade the file.
+    pub fn from_file<P: AsRef<Path>>(config_file: P) -> Result<Self> {
+        let config_file = config_file.as_ref();
+        let config_json = fs::read_to_string(config_file)
+            .map_err(|source| Error::ConfigIo { path: config_file.to_path_buf(), source })?;
+        let format = ConfigFormat::from_path(config_file);
+        let parse = || -> Result<Config> {
+            let mut value: serde_json::Value = format.parse(&config_json)?;
+            match migration::migrate(&mut value)? {
+                true => serde_json::from_value(value).map_err(|e| Error::ConfigParse(e.to_string())),
+                false => format.parse(&config_json),
+            }
+        };
+        parse().map_err(|e| match e {
+            Error::ConfigParse(message) => Error::ConfigParse(format!("{}: {}", config_file.display(), message)),
+            e => e,
+        })
+    }
+
+    /// Convert the given config file to the given format.
+    ///
+    /// The converted file is written next to the given file with the extension of the format,
+    /// and the given file is kept as a backup with `.bak` appended to its name. See [`backup_path`].
+    /// Returns the path of the converted file.
+    pub fn convert_file<P: AsRef<Path>>(config_file: P, format: ConfigFormat) -> Result<PathBuf> {
+        let config_file = config_file.as_ref();
+        if ConfigFormat::from_path(config_file) == format {
+            return Err(Error::InvalidSetting(format!(
+                "{} is already in the {:?} format",
+                config_file.display(),
+                format
+            )));
+        }
+        let converted = config_file.with_extension(format.extensions()[0]);
+        if converted.exists() {
+            return Err(Error::FileExists(converted));
+        }
+        let config = Config::from_file(config_file)?;
+        config.create_config_file(&converted)?;
+        fs::rename(config_file, backup_path(config_file, ""))
+            .map_err(|source| Error::ConfigIo { path: config_file.to_path_buf(), source })?;
+        Ok(converted)
+    }
+}
+
+/// The path of a backup of the given file, with the given tag and `.bak` appended to its name,
+/// such as `config.json.bak`. An existing backup is never overwritten,
+/// so the backup is `config.json.2.bak`, `config.json.3.bak` and so on if it exists.
+pub fn backup_path<P: AsRef<Path>>(file: P, tag: &str) -> PathBuf {
+    let file = file.as_ref().as_os_str();
+    let candidate = |n: u32| {
+        let mut backup = file.to_os_string();
+        backup.push(tag);
+        if n > 1 {
+            backup.push(format!(".{}", n));
+        }
+        backup.push(".bak");
+        PathBuf::from(backup)
+    };
+    let mut n = 1;
+    while candidate(n).exists() {
+        n += 1;
     }
+    candidate(n)
 }
 
 #[cfg(test)]
@@ -80,28 +215,162 @@ mod tests {
     fn test_set_author() {
         let mut config = Config::new();
         config.set_author("test".to_string());
-        assert_eq!(config.author.unwrap(), "test");
+        assert_eq!(config.settings.author.unwrap(), "test");
     }
 
     #[test]
     fn test_set_header() {
         let mut config = Config::new();
         config.set_header(Some("test".to_string()));
-        assert_eq!(config.header.unwrap(), "test".to_string());
+        assert_eq!(config.settings.header.unwrap(), "test".to_string());
     }
 
     #[test]
     fn test_set_footer() {
         let mut config = Config::new();
         config.set_footer(Some("test".to_string()));
-        assert_eq!(config.footer.unwrap(), "test".to_string());
+        assert_eq!(config.settings.footer.unwrap(), "test".to_string());
     }
 
     #[test]
     fn test_set_extension() {
         let mut config = Config::new();
         config.set_extension(Some("test".to_string()));
-        assert_eq!(config.extension.unwrap(), "test".to_string());
+        assert_eq!(config.settings.extension.unwrap(), "test".to_string());
+    }
+
+    #[test]
+    fn test_set_sections() {
+        let mut config = Config::new();
+        config.set_sections(Some(vec![Section::new("Action Items")]));
+        assert_eq!(config.settings.sections.unwrap(), vec![Section::new("Action Items")]);
+    }
+
+    #[test]
+    fn test_set_default_template() {
+        let mut config = Config::new();
+        config.set_default_template(Some("standup".to_string()));
+        assert_eq!(config.settings.default_template.unwrap(), "standup");
+    }
+
+    #[test]
+    fn test_set_front_matter() {
+        let mut config = Config::new();
+        config.set_front_matter(Some(FrontMatter::Yaml));
+        assert_eq!(config.settings.front_matter.unwrap(), FrontMatter::Yaml);
+    }
+
+    fn full_config() -> Config {
+        let mut config = Config::new();
+        config.set_author("John Doe".to_string());
+        config.set_header(Some("line 1\nline 2".to_string()));
+        config.set_rich(RichMetadata {
+            extra_metadata: vec![
+                "location".into(),
+                rich_metadata::ExtraField {
+                    label: "date".to_string(),
+                    field_type: rich_metadata::FieldType::Date,
+                    required: true,
+                    ..Default::default()
+                },
+            ],
+        });
+        config.set_sections(Some(vec![Section::new("Action Items")]));
+        config.set_default_template(Some("standup".to_string()));
+        config.set_front_matter(Some(FrontMatter::Toml));
+        config
+    }
+
+    #[test]
+    fn test_from_file_in_every_format() {
+        let dir = tempdir().unwrap();
+        let config = full_config();
+        for name in ["config.json", "config.toml", "config.yaml", "config.yml"] {
+            let config_file = dir.path().join(name);
+            config.create_config_file(&config_file).unwrap();
+            assert_eq!(Config::from_file(&config_file).unwrap(), config, "{}", name);
+        }
+        let toml = fs::read_to_string(dir.path().join("config.toml")).unwrap();
+        assert!(toml.contains("author = \"John Doe\""));
+    }
+
+    #[test]
+    fn test_convert_file() {
+        let dir = tempdir().unwrap();
+        let config_file = dir.path().join("config.json");
+        full_config().create_config_file(&config_file).unwrap();
+        let converted = Config::convert_file(&config_file, ConfigFormat::Toml).unwrap();
+        assert_eq!(converted, dir.path().join("config.toml"));
+        assert_eq!(Config::from_file(&converted).unwrap(), full_config());
+        assert!(!config_file.exists());
+        assert!(dir.path().join("config.json.bak").is_file());
+        assert!(Config::convert_file(&converted, ConfigFormat::Toml).is_err());
+    }
+
+    #[test]
+    fn test_convert_file_keeps_existing_backup() {
+        let dir = tempdir().unwrap();
+        let config_file = dir.path().join("config.json");
+        full_config().create_config_file(&config_file).unwrap();
+        fs::write(dir.path().join("config.json.bak"), "older backup").unwrap();
+        fs::write(dir.path().join("config.json.2.bak"), "older backup").unwrap();
+        Config::convert_file(&config_file, ConfigFormat::Yaml).unwrap();
+        assert_eq!(fs::read_to_string(dir.path().join("config.json.bak")).unwrap(), "older backup");
+        assert!(dir.path().join("config.json.3.bak").is_file());
+    }
+
+    #[test]
+    fn test_merge() {
+        let mut config = Config::new();
+        config.set_author("John Doe".to_string());
+        config.set_extension(Some("md".to_string()));
+        let mut other = Config::default();
+        other.set_author("Jane Doe".to_string());
+        other.set_front_matter(Some(FrontMatter::Yaml));
+        config.merge(other);
+        assert_eq!(config.settings.author.as_deref(), Some("Jane Doe"));
+        assert_eq!(config.settings.extension.as_deref(), Some("md"));
+        assert_eq!(config.settings.front_matter, Some(FrontMatter::Yaml));
+        assert!(config.settings.rich.is_some());
+    }
+
+    #[test]
+    fn test_merge_profiles() {
+        let mut config = Config::new();
+        config.profiles = Some(BTreeMap::from([(
+            "client".to_string(),
+            Profile {
+                settings: Settings {
+                    author: Some("John Doe".to_string()),
+                    footer: Some("bye".to_string()),
+                    ..Default::default()
+                },
+            },
+        )]));
+        let other = Config {
+            profiles: Some(BTreeMap::from([
+                (
+                    "client".to_string(),
+                    Profile { settings: Settings { author: Some("Jane Doe".to_string()), ..Default::default() } },
+                ),
+                ("internal".to_string(), Profile::default()),
+            ])),
+            ..Default::default()
+        };
+        config.merge(other);
+        let profiles = config.profiles.unwrap();
+        assert_eq!(profiles["client"].settings.author.as_deref(), Some("Jane Doe"));
+        assert_eq!(profiles["client"].settings.footer.as_deref(), Some("bye"));
+        assert!(profiles.contains_key("internal"));
+    }
+
+    #[test]
+    fn test_from_file_with_front_matter() {
+        let dir = tempdir().unwrap();
+        let config_file = dir.path().join("config.json");
+        fs::write(&config_file, r#"{"front_matter": "toml"}"#).unwrap();
+        let config_from_file = Config::from_file(&config_file).unwrap();
+        assert_eq!(config_from_file.settings.front_matter, Some(FrontMatter::Toml));
     }
 
     #[test]
@@ -144,19 +413,42 @@ mod tests {
     #[test]
     fn test_from_file_with_rich() {
         let dir = tempdir().unwrap();
-        // let config_file = dir.path().join("config.json");
+        let config_file = dir.path().join("config.json");
         let mut config = Config::new();
-        let config_file = Path::new("config_test.json");
-        match config.rich {
-            Some(ref mut rich) => {
-                rich.extra_metadata.push("test1".to_string());
-                rich.extra_metadata.push("test2".to_string());
-                rich.extra_metadata.push("test3".to_string());
-            },
-            None => {}
+        if let Some(ref mut rich) = config.settings.rich {
+            rich.extra_metadata.push("test1".into());
+            rich.extra_metadata.push("test2".into());
+            rich.extra_metadata.push("test3".into());
         }
         config.create_config_file(&config_file).unwrap();
         let config_from_file = Config::from_file(&config_file).unwrap();
         assert_eq!(config, config_from_file);
     }
+
+    #[test]
+    fn test_from_file_without_sections() {
+        let dir = tempdir().unwrap();
+        let config_file = dir.path().join("config.json");
+        fs::write(&config_file, r#"{"author": "test_author"}"#).unwrap();
+        let config_from_file = Config::from_file(&config_file).unwrap();
+        assert!(config_from_file.settings.sections.is_none());
+    }
+
+    #[test]
+    fn test_from_file_with_sections() {
+        let dir = tempdir().unwrap();
+        let config_file = dir.path().join("config.json");
+        let mut config = Config::new();
+        config.set_sections(Some(vec![
+            Section {
+                body: Some("- [ ] ".to_string()),
+                order: Some(1),
+                ..Section::new("Action Items")
+            },
+            Section::new("Decisions Made"),
+        ]));
+        config.create_config_file(&config_file).unwrap();
+        let config_from_file = Config::from_file(&config_file).unwrap();
+        assert_eq!(config, config_from_file);
+    }
 }
@@ -0,0 +1,104 @@
+//! Named profiles that override the config, such as one per client.
+//!
+//! Profiles are stored under the `profiles` key of the config file, and a profile is selected
+//! with `--profile <name>` or the `MOMI_PROFILE` environment variable.
+//! A profile has the [`Settings`] of the config file, and the settings that are provided
+//! override the corresponding settings of the [`Config`].
+//!
+
+use serde_derive::{Deserialize, Serialize};
+use crate::config::Config;
+use crate::config::settings::Settings;
+use crate::error::{Error, Result};
+
+/// The environment variable holding the name of the selected profile.
+pub const PROFILE_ENV: &str = "MOMI_PROFILE";
+
+/// A named profile such as "client" or "internal".
+#[derive(Debug, Default, PartialEq, PartialOrd, Serialize, Deserialize, Clone)]
+pub struct Profile {
+    /// The settings that override the settings of the config.
+    #[serde(flatten)]
+    pub settings: Settings,
+}
+
+impl Config {
+    /// The names of the profiles, sorted alphabetically.
+    pub fn profile_names(&self) -> Vec<String> {
+        self.profiles.iter().flat_map(|profiles| profiles.keys().cloned()).collect()
+    }
+
+    /// Override the settings of this config with the settings of the profile with the given name.
+    ///
+    /// If there is no profile with the given name, the error message lists the available profiles.
+    pub fn apply_profile(&mut self, name: &str) -> Result<()> {
+        let Some(profile) = self.profiles.as_ref().and_then(|profiles| profiles.get(name)).cloned() else {
+            let available = self.profile_names();
+            return Err(Error::UnknownProfile(match available.is_empty() {
+                true => format!("Unknown profile \"{}\". No profiles are available", name),
+                false => format!("Unknown profile \"{}\". Available profiles: {}", name, available.join(", ")),
+            }));
+        };
+        self.settings.merge(profile.settings);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+    use crate::config::front_matter::FrontMatter;
+
+    fn config_with_profiles() -> Config {
+        let mut config = Config::new();
+        config.set_author("John Doe".to_string());
+        config.set_footer(Some("footer".to_string()));
+        config.profiles = Some(BTreeMap::from([
+            (
+                "client".to_string(),
+                Profile {
+                    settings: Settings {
+                        author: Some("Jane Doe".to_string()),
+                        header: Some("Client meeting".to_string()),
+                        ..Default::default()
+                    },
+                },
+            ),
+            ("internal".to_string(), Profile::default()),
+        ]));
+        config
+    }
+
+    #[test]
+    fn test_apply_profile() {
+        let mut config = config_with_profiles();
+        config.apply_profile("client").unwrap();
+        assert_eq!(config.settings.author.as_deref(), Some("Jane Doe"));
+        assert_eq!(config.settings.header.as_deref(), Some("Client meeting"));
+        assert_eq!(config.settings.footer.as_deref(), Some("footer"));
+    }
+
+    #[test]
+    fn test_apply_unknown_profile() {
+        let mut config = config_with_profiles();
+        let error = config.apply_profile("vendor").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Unknown profile \"vendor\". Available profiles: client, internal"
+        );
+        let mut config = Config::new();
+        assert!(config.apply_profile("client").is_err());
+    }
+
+    #[test]
+    fn test_deserialize() {
+        let config: Config = serde_json::from_str(
+            r#"{"profiles": {"client": {"author": "Jane Doe", "front_matter": "yaml"}}}"#,
+        )
+        .unwrap();
+        let profile = &config.profiles.unwrap()["client"];
+        assert_eq!(profile.settings.author.as_deref(), Some("Jane Doe"));
+        assert_eq!(profile.settings.front_matter, Some(FrontMatter::Yaml));
+    }
+}
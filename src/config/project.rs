@@ -0,0 +1,115 @@
+//! Project-local configuration for the meeting conventions of a repository or project.
+//!
+//! A project is a directory that has a `.momi` directory, found like `.git` by walking up from the current directory.
+//! The `.momi` directory holds the project config and templates,
+//! and the minutes created anywhere inside the project are written to its `minutes` directory.
+//!
+//! ```text
+//! project/
+//! ├── .momi/
+//! │   ├── config.json
+//! │   └── templates/
+//! └── minutes/
+//! ```
+//!
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use crate::config::Config;
+use crate::config::format::config_file_in;
+use crate::config::layers::CONFIG_STEM;
+use crate::config::template::TEMPLATES_DIR;
+use crate::error::{Error, Result};
+
+/// The name of the directory marking a project.
+pub const PROJECT_DIR: &str = ".momi";
+/// The name of the directory the minutes of a project are written to.
+pub const MINUTES_DIR: &str = "minutes";
+
+/// A project with a `.momi` directory.
+#[derive(Debug, PartialEq)]
+pub struct Project {
+    /// The directory that has the `.momi` directory.
+    pub root: PathBuf,
+}
+
+impl Project {
+    /// Find the project of the given directory, which is the directory itself or the nearest parent directory
+    /// that has a `.momi` directory.
+    pub fn discover<P: AsRef<Path>>(dir: P) -> Option<Self> {
+        dir.as_ref()
+            .ancestors()
+            .find(|dir| dir.join(PROJECT_DIR).is_dir())
+            .map(|root| Project { root: root.to_path_buf() })
+    }
+
+    /// Create the `.momi` directory with an empty config file and templates directory,
+    /// and the minutes directory in the given directory.
+    ///
+    /// It is an error if the directory is already a project.
+    pub fn init<P: AsRef<Path>>(root: P) -> Result<Self> {
+        let project = Project { root: root.as_ref().to_path_buf() };
+        if project.dir().exists() {
+            return Err(Error::FileExists(project.dir()));
+        }
+        for dir in [project.templates_dir(), project.minutes_dir()] {
+            fs::create_dir_all(&dir).map_err(|source| Error::ConfigIo { path: dir.clone(), source })?;
+        }
+        Config::default().create_config_file(project.config_file())?;
+        Ok(project)
+    }
+
+    /// The `.momi` directory of the project.
+    pub fn dir(&self) -> PathBuf {
+        self.root.join(PROJECT_DIR)
+    }
+
+    /// The config file of the project, `config.json` if there is none in another format.
+    pub fn config_file(&self) -> PathBuf {
+        config_file_in(self.dir(), CONFIG_STEM)
+    }
+
+    /// The templates directory of the project.
+    pub fn templates_dir(&self) -> PathBuf {
+        self.dir().join(TEMPLATES_DIR)
+    }
+
+    /// The directory the minutes of the project are written to.
+    pub fn minutes_dir(&self) -> PathBuf {
+        self.root.join(MINUTES_DIR)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::layers::CONFIG_FILE;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_init_and_discover() {
+        let dir = tempdir().unwrap();
+        let nested = dir.path().join("docs").join("2024");
+        fs::create_dir_all(&nested).unwrap();
+        assert_eq!(Project::discover(&nested), None);
+
+        let project = Project::init(dir.path()).unwrap();
+        assert!(project.config_file().is_file());
+        assert!(project.templates_dir().is_dir());
+        assert!(project.minutes_dir().is_dir());
+        assert_eq!(Config::from_file(project.config_file()).unwrap(), Config::default());
+        assert_eq!(Project::discover(&nested), Some(project));
+    }
+
+    #[test]
+    fn test_init_twice() {
+        let dir = tempdir().unwrap();
+        Project::init(dir.path()).unwrap();
+        fs::write(dir.path().join(PROJECT_DIR).join(CONFIG_FILE), r#"{"author": "John Doe"}"#).unwrap();
+        assert!(Project::init(dir.path()).is_err());
+        assert_eq!(
+            fs::read_to_string(dir.path().join(PROJECT_DIR).join(CONFIG_FILE)).unwrap(),
+            r#"{"author": "John Doe"}"#
+        );
+    }
+}
@@ -1,14 +1,271 @@
 //! Rich metadata that contains extra metadata which is not part of the standard metadata.
 //!
 
+use chrono::{NaiveDate, NaiveDateTime};
 use serde_derive::{Deserialize, Serialize};
 
 /// Rich metadata struct that contains extra metadata which is not part of the standard metadata.
 ///
-/// The `extra_metadata` field is a vector of strings that can hold any additional metadata fields.
+/// The `extra_metadata` field is a vector of fields that can hold any additional metadata.
+/// Each field can be written as a plain label string or as an object declaring its type.
 #[derive(Debug, Default, PartialEq, PartialOrd, Serialize, Deserialize, Clone)]
 pub struct RichMetadata {
     /// Extra metadata fields that are not part of the standard metadata.
-    pub extra_metadata: Vec<String>,
+    pub extra_metadata: Vec<ExtraField>,
 }
 
+/// The labels of the standard metadata, which extra metadata fields cannot use
+/// because they would be written twice in the front matter.
+pub const RESERVED_LABELS: [&str; 3] = ["title", "created", "author"];
+
+/// The type of the value of an extra metadata field.
+#[derive(Debug, Default, PartialEq, PartialOrd, Serialize, Deserialize, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum FieldType {
+    /// Any text.
+    #[default]
+    Text,
+    /// A date formatted as `%Y-%m-%d`.
+    Date,
+    /// A date and time formatted as `%Y-%m-%d %H:%M` or `%Y-%m-%d %H:%M:%S`.
+    Datetime,
+    /// A comma separated list of people.
+    People,
+    /// One of the allowed `values` of the field.
+    Enum,
+    /// `true` or `false`. `yes` and `no` are accepted too.
+    Boolean,
+    /// A number.
+    Number,
+}
+
+/// An extra metadata field such as `location`, `attendees` or `meeting type`.
+///
+/// A field written as a plain string in the config file is a text field with the string as its label,
+/// so the config files written before the fields were typed keep working.
+/// A field is always written back as a typed field.
+#[derive(Debug, Default, PartialEq, PartialOrd, Serialize, Deserialize, Clone)]
+#[serde(from = "ExtraFieldRepr", into = "ExtraFieldRepr")]
+pub struct ExtraField {
+    /// The label of the field written to the document.
+    pub label: String,
+    /// The type of the value of the field.
+    pub field_type: FieldType,
+    /// The value used when no value is given.
+    pub default: Option<String>,
+    /// Whether the field must have a value when it is written to the document.
+    pub required: bool,
+    /// The help text shown when the value of the field is asked.
+    pub help: Option<String>,
+    /// The allowed values of an enum field.
+    pub values: Option<Vec<String>>,
+}
+
+/// The representation of [`ExtraField`] in the config file.
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+enum ExtraFieldRepr {
+    Label(String),
+    Typed {
+        label: String,
+        #[serde(rename = "type", default)]
+        field_type: FieldType,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        default: Option<String>,
+        #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+        required: bool,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        help: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        values: Option<Vec<String>>,
+    },
+}
+
+impl From<ExtraFieldRepr> for ExtraField {
+    fn from(repr: ExtraFieldRepr) -> Self {
+        match repr {
+            ExtraFieldRepr::Label(label) => ExtraField::from(label),
+            ExtraFieldRepr::Typed { label, field_type, default, required, help, values } => ExtraField {
+                label,
+                field_type,
+                default,
+                required,
+                help,
+                values,
+            },
+        }
+    }
+}
+
+impl From<ExtraField> for ExtraFieldRepr {
+    fn from(field: ExtraField) -> Self {
+        ExtraFieldRepr::Typed {
+            label: field.label,
+            field_type: field.field_type,
+            default: field.default,
+            required: field.required,
+            help: field.help,
+            values: field.values,
+        }
+    }
+}
+
+impl From<String> for ExtraField {
+    fn from(label: String) -> Self {
+        ExtraField {
+            label,
+            ..Default::default()
+        }
+    }
+}
+
+impl From<&str> for ExtraField {
+    fn from(label: &str) -> Self {
+        ExtraField::from(label.to_string())
+    }
+}
+
+impl ExtraField {
+    /// Validate the given value against the type of the field and return the normalized value.
+    ///
+    /// An empty value is valid unless the field is required.
+    pub fn validate(&self, value: &str) -> Result<String, String> {
+        let value = value.trim();
+        if value.is_empty() {
+            return match self.required {
+                true => Err(format!("\"{}\" is required", self.label)),
+                false => Ok(String::new()),
+            };
+        }
+        match self.field_type {
+            FieldType::Text => Ok(value.to_string()),
+            FieldType::Date => NaiveDate::parse_from_str(value, "%Y-%m-%d")
+                .map(|date| date.format("%Y-%m-%d").to_string())
+                .map_err(|_| format!("\"{}\" must be a date like 2024-05-02, got \"{}\"", self.label, value)),
+            FieldType::Datetime => NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M:%S")
+                .or_else(|_| NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M"))
+                .map(|datetime| datetime.format("%Y-%m-%d %H:%M:%S").to_string())
+                .map_err(|_| {
+                    format!("\"{}\" must be a date and time like 2024-05-02 09:30, got \"{}\"", self.label, value)
+                }),
+            FieldType::People => Ok(value
+                .split(',')
+                .map(|person| person.trim())
+                .filter(|person| !person.is_empty())
+                .collect::<Vec<&str>>()
+                .join(", ")),
+            FieldType::Enum => {
+                let values = self.values.clone().unwrap_or_default();
+                match values.iter().any(|allowed| allowed == value) {
+                    true => Ok(value.to_string()),
+                    false => Err(format!(
+                        "\"{}\" must be one of {}, got \"{}\"",
+                        self.label,
+                        values.join(", "),
+                        value
+                    )),
+                }
+            }
+            FieldType::Boolean => match value.to_lowercase().as_str() {
+                "true" | "yes" | "y" => Ok("true".to_string()),
+                "false" | "no" | "n" => Ok("false".to_string()),
+                _ => Err(format!("\"{}\" must be true or false, got \"{}\"", self.label, value)),
+            },
+            FieldType::Number => value
+                .parse::<f64>()
+                .ok()
+                .filter(|number| number.is_finite())
+                .map(|_| value.to_string())
+                .ok_or_else(|| format!("\"{}\" must be a number, got \"{}\"", self.label, value)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_deserialize_plain_and_typed_fields() {
+        let rich: RichMetadata = serde_json::from_str(
+            r#"{
+                "extra_metadata": [
+                    "location",
+                    { "label": "attendees", "type": "people", "required": true },
+                    { "label": "meeting type", "type": "enum", "values": ["standup", "retro"], "default": "standup" }
+                ]
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(rich.extra_metadata[0], ExtraField::from("location"));
+        assert_eq!(rich.extra_metadata[1].field_type, FieldType::People);
+        assert!(rich.extra_metadata[1].required);
+        assert_eq!(rich.extra_metadata[2].default.as_deref(), Some("standup"));
+    }
+
+    #[test]
+    fn test_serialize_plain_field_as_typed_field() {
+        let rich = RichMetadata {
+            extra_metadata: vec![
+                ExtraField::from("location"),
+                ExtraField {
+                    field_type: FieldType::Date,
+                    ..ExtraField::from("due")
+                },
+            ],
+        };
+        assert_eq!(
+            serde_json::to_string(&rich).unwrap(),
+            r#"{"extra_metadata":[{"label":"location","type":"text"},{"label":"due","type":"date"}]}"#
+        );
+    }
+
+    #[test]
+    fn test_validate_required() {
+        let field = ExtraField {
+            required: true,
+            ..ExtraField::from("location")
+        };
+        assert!(field.validate(" ").is_err());
+        assert_eq!(ExtraField::from("location").validate("").unwrap(), "");
+    }
+
+    #[test]
+    fn test_validate_types() {
+        let field = |field_type| ExtraField {
+            field_type,
+            ..ExtraField::from("field")
+        };
+        assert_eq!(field(FieldType::Date).validate("2024-05-02").unwrap(), "2024-05-02");
+        assert!(field(FieldType::Date).validate("05/02/2024").is_err());
+        assert_eq!(
+            field(FieldType::Datetime).validate("2024-05-02 09:30").unwrap(),
+            "2024-05-02 09:30:00"
+        );
+        assert_eq!(
+            field(FieldType::People).validate("John,  Jane ,").unwrap(),
+            "John, Jane"
+        );
+        assert_eq!(field(FieldType::Boolean).validate("Yes").unwrap(), "true");
+        assert!(field(FieldType::Boolean).validate("maybe").is_err());
+        assert_eq!(field(FieldType::Number).validate("3.5").unwrap(), "3.5");
+        assert!(field(FieldType::Number).validate("three").is_err());
+        for value in ["NaN", "inf", "-inf", "infinity"] {
+            assert!(field(FieldType::Number).validate(value).is_err(), "{}", value);
+        }
+    }
+
+    #[test]
+    fn test_validate_enum() {
+        let field = ExtraField {
+            field_type: FieldType::Enum,
+            values: Some(vec!["standup".to_string(), "retro".to_string()]),
+            ..ExtraField::from("meeting type")
+        };
+        assert_eq!(field.validate("retro").unwrap(), "retro");
+        assert_eq!(
+            field.validate("review").unwrap_err(),
+            "\"meeting type\" must be one of standup, retro, got \"review\""
+        );
+    }
+}
@@ -0,0 +1,62 @@
+//! Sections of the document that are written between the header and the footer.
+//!
+
+use serde_derive::{Deserialize, Serialize};
+
+/// A section of the document such as "Action Items", "Decisions Made" or "Next Steps".
+///
+/// The `heading` is written as a Markdown heading for `.md` files
+/// and as an underlined heading for any other extension.
+/// The optional `body` is written below the heading as a placeholder.
+#[derive(Debug, Default, PartialEq, PartialOrd, Serialize, Deserialize, Clone)]
+pub struct Section {
+    /// The heading text of the section.
+    pub heading: String,
+    /// The placeholder body of the section.
+    pub body: Option<String>,
+    /// The position of the section in the document.
+    /// Sections without an order are written after the ordered ones, in the order they are listed.
+    pub order: Option<i32>,
+}
+
+impl Section {
+    pub fn new(heading: &str) -> Self {
+        Section {
+            heading: heading.to_string(),
+            ..Default::default()
+        }
+    }
+
+    /// Sort the given sections by their order.
+    /// The sort is stable, so sections with the same order keep the order they are listed in.
+    pub fn sort(sections: &mut [Section]) {
+        sections.sort_by_key(|section| section.order.unwrap_or(i32::MAX));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sort() {
+        let mut sections = vec![
+            Section::new("Next Steps"),
+            Section {
+                order: Some(2),
+                ..Section::new("Decisions Made")
+            },
+            Section::new("Conclusion"),
+            Section {
+                order: Some(1),
+                ..Section::new("Action Items")
+            },
+        ];
+        Section::sort(&mut sections);
+        let headings: Vec<&str> = sections.iter().map(|s| s.heading.as_str()).collect();
+        assert_eq!(
+            headings,
+            vec!["Action Items", "Decisions Made", "Next Steps", "Conclusion"]
+        );
+    }
+}
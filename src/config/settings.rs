@@ -0,0 +1,97 @@
+//! The settings of the document that the config file, a profile and a template can provide.
+//!
+//! [`Settings`] is flattened into [`Config`](super::Config), [`Profile`](super::profile::Profile)
+//! and [`Template`](super::template::Template), so they are written with the same keys
+//! and a later one overrides an earlier one with [`Settings::merge`].
+//!
+
+use serde_derive::{Deserialize, Serialize};
+use crate::config::front_matter::FrontMatter;
+use crate::config::rich_metadata::RichMetadata;
+use crate::config::section::Section;
+
+/// The settings of the document. Every setting is optional, and a missing one is neither written nor overridden.
+#[derive(Debug, Default, PartialEq, PartialOrd, Serialize, Deserialize, Clone)]
+pub struct Settings {
+    /// The author of the document.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub author: Option<String>,
+    /// The header of the document.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub header: Option<String>,
+    /// The footer of the document.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub footer: Option<String>,
+    /// The extension of the document.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub extension: Option<String>,
+    /// The custom metadata that can be added to the document.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rich: Option<RichMetadata>,
+    /// The sections written between the header and the footer of the document.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sections: Option<Vec<Section>>,
+    /// The name of the template used when no template is given on the command line.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub default_template: Option<String>,
+    /// The format of the front matter that holds the metadata of the document.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub front_matter: Option<FrontMatter>,
+    /// The directory the documents are written to, which can have placeholders such as `minutes/{{year}}/{{month}}`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub output_dir: Option<String>,
+    /// The filename of the documents without the extension, such as `{{date:%Y-%m-%d}}_{{slug title}}`.
+    /// The title given on the command line is still the title of the document.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub filename_pattern: Option<String>,
+}
+
+impl Settings {
+    /// Override the settings with the settings that are set in the given settings.
+    pub fn merge(&mut self, other: Settings) {
+        let Settings {
+            author,
+            header,
+            footer,
+            extension,
+            rich,
+            sections,
+            default_template,
+            front_matter,
+            output_dir,
+            filename_pattern,
+        } = other;
+        self.author = author.or(self.author.take());
+        self.header = header.or(self.header.take());
+        self.footer = footer.or(self.footer.take());
+        self.extension = extension.or(self.extension.take());
+        self.rich = rich.or(self.rich.take());
+        self.sections = sections.or(self.sections.take());
+        self.default_template = default_template.or(self.default_template.take());
+        self.front_matter = front_matter.or(self.front_matter.take());
+        self.output_dir = output_dir.or(self.output_dir.take());
+        self.filename_pattern = filename_pattern.or(self.filename_pattern.take());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_merge() {
+        let mut settings = Settings {
+            author: Some("John Doe".to_string()),
+            footer: Some("footer".to_string()),
+            ..Default::default()
+        };
+        settings.merge(Settings {
+            author: Some("Jane Doe".to_string()),
+            front_matter: Some(FrontMatter::Yaml),
+            ..Default::default()
+        });
+        assert_eq!(settings.author.as_deref(), Some("Jane Doe"));
+        assert_eq!(settings.footer.as_deref(), Some("footer"));
+        assert_eq!(settings.front_matter, Some(FrontMatter::Yaml));
+    }
+}
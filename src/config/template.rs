@@ -0,0 +1,211 @@
+//! Named templates that override the layout of the document.
+//!
+//! Templates are stored as `<name>.toml`, `<name>.yaml`, `<name>.yml` or `<name>.json` files
+//! in the `templates` directory next to a config file, in the format given by the extension.
+//! If a template has files in several formats, the one that comes first in [`ConfigFormat::PRIORITY`] is used.
+//! If several directories have a template with the same name, the one next to the config file with the highest precedence is used.
+//! A template has the [`Settings`] of the config file except `default_template`,
+//! and the settings that are provided override the corresponding settings of the [`Config`].
+//!
+
+use serde_derive::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+use crate::config::Config;
+use crate::config::format::{config_file_in, ConfigFormat};
+use crate::config::settings::Settings;
+use crate::error::{Error, Result};
+
+/// The name of the directory holding the templates.
+pub const TEMPLATES_DIR: &str = "templates";
+
+/// A named template such as "standup", "retro" or "design-review".
+#[derive(Debug, Default, PartialEq, PartialOrd, Serialize, Deserialize, Clone)]
+pub struct Template {
+    /// The settings that override the settings of the config.
+    #[serde(flatten)]
+    pub settings: Settings,
+}
+
+impl Template {
+    /// List the names of the templates in the given directory, sorted alphabetically.
+    /// If the directory does not exist, there are no templates.
+    pub fn list<P: AsRef<Path>>(templates_dir: P) -> Vec<String> {
+        let mut names: Vec<String> = match fs::read_dir(templates_dir) {
+            Ok(entries) => entries
+                .filter_map(|entry| entry.ok())
+                .map(|entry| entry.path())
+                .filter(|path| {
+                    path.extension().and_then(|ext| ext.to_str()).is_some_and(|ext| {
+                        ConfigFormat::PRIORITY.iter().any(|format| format.extensions().contains(&ext))
+                    })
+                })
+                .filter_map(|path| {
+                    path.file_stem()
+                        .and_then(|stem| stem.to_str())
+                        .map(|stem| stem.to_string())
+                })
+                .collect(),
+            Err(_) => vec![],
+        };
+        names.sort();
+        names.dedup();
+        names
+    }
+
+    /// List the names of the templates in the given directories, sorted alphabetically.
+    pub fn list_all<P: AsRef<Path>>(templates_dirs: &[P]) -> Vec<String> {
+        let mut names: Vec<String> = templates_dirs.iter().flat_map(Template::list).collect();
+        names.sort();
+        names.dedup();
+        names
+    }
+
+    /// Load the template with the given name from the given directory.
+    ///
+    /// If there is no template with the given name,
+    /// the error message lists the available templates.
+    pub fn from_dir<P: AsRef<Path>>(templates_dir: P, name: &str) -> Result<Self> {
+        Template::from_dirs(&[templates_dir], name)
+    }
+
+    /// Load the template with the given name from the first of the given directories that has it.
+    pub fn from_dirs<P: AsRef<Path>>(templates_dirs: &[P], name: &str) -> Result<Self> {
+        let Some(templates_dir) = templates_dirs
+            .iter()
+            .find(|dir| Template::list(dir).iter().any(|template| template == name))
+        else {
+            let available = Template::list_all(templates_dirs);
+            return Err(Error::UnknownTemplate(match available.is_empty() {
+                true => format!("Unknown template \"{}\". No templates are available", name),
+                false => format!(
+                    "Unknown template \"{}\". Available templates: {}",
+                    name,
+                    available.join(", ")
+                ),
+            }));
+        };
+        let template_file = config_file_in(templates_dir, name);
+        let template_text = fs::read_to_string(&template_file)
+            .map_err(|source| Error::ConfigIo { path: template_file.clone(), source })?;
+        let template: Template = ConfigFormat::from_path(&template_file)
+            .parse(&template_text)
+            .map_err(|e| Error::ConfigParse(format!("{}: {}", template_file.display(), e)))?;
+        // A template is selected by name, so it cannot select another template.
+        if template.settings.default_template.is_some() {
+            return Err(Error::ConfigParse(format!(
+                "{}: A template cannot have a default_template",
+                template_file.display()
+            )));
+        }
+        Ok(template)
+    }
+
+    /// Override the settings of the given config with the settings provided in this template.
+    pub fn apply_to(&self, config: &mut Config) {
+        config.settings.merge(self.settings.clone());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+    use crate::config::section::Section;
+
+    fn template(settings: Settings) -> Template {
+        Template { settings }
+    }
+
+    fn write_template(dir: &Path, name: &str, template: &Template) {
+        fs::write(
+            dir.join(format!("{}.json", name)),
+            serde_json::to_string_pretty(template).unwrap(),
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_list() {
+        let dir = tempdir().unwrap();
+        write_template(dir.path(), "standup", &Template::default());
+        write_template(dir.path(), "retro", &Template::default());
+        fs::write(dir.path().join("notes.txt"), "not a template").unwrap();
+        assert_eq!(Template::list(dir.path()), vec!["retro", "standup"]);
+    }
+
+    #[test]
+    fn test_from_dirs() {
+        let user = tempdir().unwrap();
+        let system = tempdir().unwrap();
+        let user_template = template(Settings { header: Some("user".to_string()), ..Default::default() });
+        write_template(user.path(), "standup", &user_template);
+        write_template(system.path(), "standup", &Template::default());
+        write_template(system.path(), "retro", &Template::default());
+        let dirs = [user.path(), system.path()];
+        assert_eq!(Template::from_dirs(&dirs, "standup").unwrap(), user_template);
+        assert_eq!(Template::from_dirs(&dirs, "retro").unwrap(), Template::default());
+        assert_eq!(Template::list_all(&dirs), vec!["retro", "standup"]);
+    }
+
+    #[test]
+    fn test_list_with_missing_dir() {
+        let dir = tempdir().unwrap();
+        assert!(Template::list(dir.path().join(TEMPLATES_DIR)).is_empty());
+    }
+
+    #[test]
+    fn test_from_dir() {
+        let dir = tempdir().unwrap();
+        let standup = template(Settings {
+            header: Some("standup header".to_string()),
+            sections: Some(vec![Section::new("Yesterday"), Section::new("Today")]),
+            ..Default::default()
+        });
+        write_template(dir.path(), "standup", &standup);
+        assert_eq!(Template::from_dir(dir.path(), "standup").unwrap(), standup);
+        let nested = template(Settings { default_template: Some("standup".to_string()), ..Default::default() });
+        write_template(dir.path(), "nested", &nested);
+        assert!(matches!(Template::from_dir(dir.path(), "nested"), Err(Error::ConfigParse(_))));
+    }
+
+    #[test]
+    fn test_from_dir_with_toml_and_yaml() {
+        let dir = tempdir().unwrap();
+        fs::write(dir.path().join("standup.toml"), "header = \"toml header\"\n").unwrap();
+        fs::write(dir.path().join("standup.json"), r#"{"header": "json header"}"#).unwrap();
+        fs::write(dir.path().join("retro.yml"), "header: yaml header\n").unwrap();
+        assert_eq!(Template::list(dir.path()), vec!["retro", "standup"]);
+        let header = |name| Template::from_dir(dir.path(), name).unwrap().settings.header.unwrap();
+        assert_eq!(header("standup"), "toml header");
+        assert_eq!(header("retro"), "yaml header");
+    }
+
+    #[test]
+    fn test_from_dir_with_unknown_name() {
+        let dir = tempdir().unwrap();
+        write_template(dir.path(), "standup", &Template::default());
+        write_template(dir.path(), "retro", &Template::default());
+        let error = Template::from_dir(dir.path(), "review").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Unknown template \"review\". Available templates: retro, standup"
+        );
+    }
+
+    #[test]
+    fn test_apply_to() {
+        let mut config = Config::new();
+        config.set_header(Some("config header".to_string()));
+        config.set_footer(Some("config footer".to_string()));
+        let template = template(Settings {
+            header: Some("template header".to_string()),
+            sections: Some(vec![Section::new("Action Items")]),
+            ..Default::default()
+        });
+        template.apply_to(&mut config);
+        assert_eq!(config.settings.header.unwrap(), "template header");
+        assert_eq!(config.settings.footer.unwrap(), "config footer");
+        assert_eq!(config.settings.sections.unwrap(), vec![Section::new("Action Items")]);
+    }
+}
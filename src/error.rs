@@ -0,0 +1,157 @@
+//! The errors of momi.
+//!
+//! Every error has its own exit code, so scripts can react to a failure without reading the message.
+//!
+//! | Error | Exit code |
+//! |---|---|
+//! | [`Error::ConfigParse`] | 3 |
+//! | [`Error::ConfigIo`] | 4 |
+//! | [`Error::InvalidSetting`] | 5 |
+//! | [`Error::FileExists`] | 6 |
+//! | [`Error::InvalidFilename`] | 7 |
+//! | [`Error::UnknownTemplate`] | 8 |
+//! | [`Error::UnknownProfile`] | 9 |
+//! | [`Error::InvalidField`] | 10 |
+//! | [`Error::Render`] | 11 |
+//! | [`Error::DocumentParse`] | 12 |
+//! | [`Error::Write`] | 13 |
+//! | [`Error::Open`] | 14 |
+//! | [`Error::Io`] | 15 |
+//!
+//! Exit code 1 is not used by momi, and exit code 2 is used for invalid command line arguments.
+//!
+
+use std::fmt;
+use std::io;
+use std::path::PathBuf;
+
+/// The result of the operations of momi.
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// An error of momi.
+#[derive(Debug)]
+pub enum Error {
+    /// A config file or template could not be parsed, or has invalid settings.
+    ConfigParse(String),
+    /// A config file or template could not be read or written.
+    ConfigIo { path: PathBuf, source: io::Error },
+    /// An unknown key or invalid value was given to `momi config`, or the settings or options cannot be used together.
+    InvalidSetting(String),
+    /// The file to create already exists.
+    FileExists(PathBuf),
+    /// The filename of the new document has no name to use as the title.
+    InvalidFilename(String),
+    /// There is no template with the given name.
+    UnknownTemplate(String),
+    /// There is no profile with the given name.
+    UnknownProfile(String),
+    /// The value of an extra metadata field is invalid for its type.
+    InvalidField(String),
+    /// The placeholders or the front matter of the document could not be rendered.
+    Render(String),
+    /// The document could not be parsed.
+    DocumentParse(String),
+    /// The document could not be written.
+    Write { path: PathBuf, source: io::Error },
+    /// The document could not be opened with the default program.
+    Open { path: PathBuf, message: String },
+    /// Any other I/O error.
+    Io(io::Error),
+}
+
+impl Error {
+    /// The exit code of the error.
+    pub fn exit_code(&self) -> u8 {
+        match self {
+            Error::ConfigParse(_) => 3,
+            Error::ConfigIo { .. } => 4,
+            Error::InvalidSetting(_) => 5,
+            Error::FileExists(_) => 6,
+            Error::InvalidFilename(_) => 7,
+            Error::UnknownTemplate(_) => 8,
+            Error::UnknownProfile(_) => 9,
+            Error::InvalidField(_) => 10,
+            Error::Render(_) => 11,
+            Error::DocumentParse(_) => 12,
+            Error::Write { .. } => 13,
+            Error::Open { .. } => 14,
+            Error::Io(_) => 15,
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::ConfigParse(message) => write!(f, "{}", message),
+            Error::ConfigIo { path, source } => write!(f, "Error accessing {}: {}", path.display(), source),
+            Error::InvalidSetting(message) => write!(f, "{}", message),
+            Error::FileExists(path) => write!(f, "{} already exists", path.display()),
+            Error::InvalidFilename(filename) => write!(f, "Invalid filename \"{}\"", filename),
+            Error::UnknownTemplate(message) => write!(f, "{}", message),
+            Error::UnknownProfile(message) => write!(f, "{}", message),
+            Error::InvalidField(message) => write!(f, "Invalid extra metadata: {}", message),
+            Error::Render(message) => write!(f, "Error rendering the document: {}", message),
+            Error::DocumentParse(message) => write!(f, "{}", message),
+            Error::Write { path, source } => write!(f, "Error writing {}: {}", path.display(), source),
+            Error::Open { path, message } => write!(f, "Error opening {}: {}", path.display(), message),
+            Error::Io(source) => write!(f, "{}", source),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::ConfigIo { source, .. } | Error::Write { source, .. } | Error::Io(source) => Some(source),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(error: io::Error) -> Self {
+        Error::Io(error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_exit_codes_are_distinct() {
+        let errors = [
+            Error::ConfigParse(String::new()),
+            Error::ConfigIo { path: PathBuf::new(), source: io::Error::other("") },
+            Error::InvalidSetting(String::new()),
+            Error::FileExists(PathBuf::new()),
+            Error::InvalidFilename(String::new()),
+            Error::UnknownTemplate(String::new()),
+            Error::UnknownProfile(String::new()),
+            Error::InvalidField(String::new()),
+            Error::Render(String::new()),
+            Error::DocumentParse(String::new()),
+            Error::Write { path: PathBuf::new(), source: io::Error::other("") },
+            Error::Open { path: PathBuf::new(), message: String::new() },
+            Error::Io(io::Error::other("")),
+        ];
+        let mut codes: Vec<u8> = errors.iter().map(Error::exit_code).collect();
+        codes.sort();
+        codes.dedup();
+        assert_eq!(codes.len(), errors.len());
+        assert!(codes.iter().all(|code| *code > 2));
+    }
+
+    #[test]
+    fn test_display() {
+        assert_eq!(
+            Error::FileExists(PathBuf::from("minutes.md")).to_string(),
+            "minutes.md already exists"
+        );
+        assert_eq!(
+            Error::InvalidFilename("..".to_string()).to_string(),
+            "Invalid filename \"..\""
+        );
+    }
+}
@@ -0,0 +1,333 @@
+//! Export to a Word document (`.docx`).
+//!
+//! The document is an Office Open XML zip written without any external tool:
+//! a title, a table of the metadata, a `Heading 1` for every section and the footer.
+//! Lists are bulleted paragraphs and the items of a checklist start with `☐` or `☒`.
+//!
+
+use std::io::{Cursor, Write};
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipWriter};
+use crate::error::{Error, Result};
+use crate::export::{escape, Block};
+use crate::metadata::metadata::Metadata;
+use crate::metadata::renderer::Renderer;
+
+/// The width of the label column of the metadata table, in twentieths of a point.
+const LABEL_WIDTH: u32 = 2400;
+
+/// The width of the value column of the metadata table, in twentieths of a point.
+const VALUE_WIDTH: u32 = 6600;
+
+/// The types of the parts of the package.
+const CONTENT_TYPES: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<Types xmlns="http://schemas.openxmlformats.org/package/2006/content-types">
+<Default Extension="rels" ContentType="application/vnd.openxmlformats-package.relationships+xml"/>
+<Default Extension="xml" ContentType="application/xml"/>
+<Override PartName="/word/document.xml" ContentType="application/vnd.openxmlformats-officedocument.wordprocessingml.document.main+xml"/>
+<Override PartName="/word/styles.xml" ContentType="application/vnd.openxmlformats-officedocument.wordprocessingml.styles+xml"/>
+<Override PartName="/word/numbering.xml" ContentType="application/vnd.openxmlformats-officedocument.wordprocessingml.numbering+xml"/>
+<Override PartName="/docProps/core.xml" ContentType="application/vnd.openxmlformats-package.core-properties+xml"/>
+</Types>
+"#;
+
+/// The relationships of the package to the document and its properties.
+const RELATIONSHIPS: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships">
+<Relationship Id="rId1" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/officeDocument" Target="word/document.xml"/>
+<Relationship Id="rId2" Type="http://schemas.openxmlformats.org/package/2006/relationships/metadata/core-properties" Target="docProps/core.xml"/>
+</Relationships>
+"#;
+
+/// The relationships of the document to its styles and numbering.
+const DOCUMENT_RELATIONSHIPS: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships">
+<Relationship Id="rId1" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/styles" Target="styles.xml"/>
+<Relationship Id="rId2" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/numbering" Target="numbering.xml"/>
+</Relationships>
+"#;
+
+/// The styles of the title, the section headings and the metadata table.
+const STYLES: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<w:styles xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main">
+<w:docDefaults>
+<w:rPrDefault><w:rPr><w:rFonts w:ascii="Calibri" w:hAnsi="Calibri" w:eastAsia="Malgun Gothic" w:cs="Calibri"/><w:sz w:val="22"/><w:lang w:val="en-US" w:eastAsia="ko-KR"/></w:rPr></w:rPrDefault>
+<w:pPrDefault><w:pPr><w:spacing w:after="120" w:line="276" w:lineRule="auto"/></w:pPr></w:pPrDefault>
+</w:docDefaults>
+<w:style w:type="paragraph" w:default="1" w:styleId="Normal"><w:name w:val="Normal"/><w:qFormat/></w:style>
+<w:style w:type="paragraph" w:styleId="Title"><w:name w:val="Title"/><w:basedOn w:val="Normal"/><w:next w:val="Normal"/><w:qFormat/><w:pPr><w:spacing w:after="240"/></w:pPr><w:rPr><w:b/><w:sz w:val="48"/></w:rPr></w:style>
+<w:style w:type="paragraph" w:styleId="Heading1"><w:name w:val="heading 1"/><w:basedOn w:val="Normal"/><w:next w:val="Normal"/><w:qFormat/><w:pPr><w:keepNext/><w:spacing w:before="360" w:after="120"/><w:outlineLvl w:val="0"/></w:pPr><w:rPr><w:b/><w:sz w:val="30"/></w:rPr></w:style>
+<w:style w:type="paragraph" w:styleId="ListParagraph"><w:name w:val="List Paragraph"/><w:basedOn w:val="Normal"/><w:qFormat/><w:pPr><w:spacing w:after="60"/><w:ind w:left="720"/></w:pPr></w:style>
+<w:style w:type="table" w:default="1" w:styleId="TableNormal"><w:name w:val="Normal Table"/><w:tblPr><w:tblInd w:w="0" w:type="dxa"/><w:tblCellMar><w:left w:w="108" w:type="dxa"/><w:right w:w="108" w:type="dxa"/></w:tblCellMar></w:tblPr></w:style>
+<w:style w:type="table" w:styleId="TableGrid"><w:name w:val="Table Grid"/><w:basedOn w:val="TableNormal"/><w:tblPr><w:tblBorders><w:top w:val="single" w:sz="4" w:space="0" w:color="BFBFBF"/><w:left w:val="single" w:sz="4" w:space="0" w:color="BFBFBF"/><w:bottom w:val="single" w:sz="4" w:space="0" w:color="BFBFBF"/><w:right w:val="single" w:sz="4" w:space="0" w:color="BFBFBF"/><w:insideH w:val="single" w:sz="4" w:space="0" w:color="BFBFBF"/><w:insideV w:val="single" w:sz="4" w:space="0" w:color="BFBFBF"/></w:tblBorders></w:tblPr></w:style>
+</w:styles>
+"#;
+
+/// The bullets of the lists.
+const NUMBERING: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<w:numbering xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main">
+<w:abstractNum w:abstractNumId="0"><w:multiLevelType w:val="singleLevel"/><w:lvl w:ilvl="0"><w:start w:val="1"/><w:numFmt w:val="bullet"/><w:lvlText w:val="•"/><w:lvlJc w:val="left"/><w:pPr><w:ind w:left="720" w:hanging="360"/></w:pPr></w:lvl></w:abstractNum>
+<w:num w:numId="1"><w:abstractNumId w:val="0"/></w:num>
+</w:numbering>
+"#;
+
+/// A Word document.
+pub struct Docx;
+
+impl Renderer for Docx {
+    fn render(&self, metadata: &Metadata, writer: &mut dyn Write) -> Result<()> {
+        // A zip is written back to front, so the package is built in memory before it is written.
+        let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+        let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+        let parts = [
+            ("[Content_Types].xml", CONTENT_TYPES.to_string()),
+            ("_rels/.rels", RELATIONSHIPS.to_string()),
+            ("docProps/core.xml", core_properties(metadata)),
+            ("word/_rels/document.xml.rels", DOCUMENT_RELATIONSHIPS.to_string()),
+            ("word/document.xml", document(metadata)),
+            ("word/styles.xml", STYLES.to_string()),
+            ("word/numbering.xml", NUMBERING.to_string()),
+        ];
+        for (name, content) in parts {
+            zip.start_file(name, options).map_err(zip_error)?;
+            zip.write_all(content.as_bytes())?;
+        }
+        let package = zip.finish().map_err(zip_error)?.into_inner();
+
+        writer.write_all(&package)?;
+        writer.flush()?;
+        Ok(())
+    }
+}
+
+/// The title, the author and the creation date of the document.
+fn core_properties(metadata: &Metadata) -> String {
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<cp:coreProperties xmlns:cp="http://schemas.openxmlformats.org/package/2006/metadata/core-properties" xmlns:dc="http://purl.org/dc/elements/1.1/" xmlns:dcterms="http://purl.org/dc/terms/" xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance">
+<dc:title>{}</dc:title>
+<dc:creator>{}</dc:creator>
+<dcterms:created xsi:type="dcterms:W3CDTF">{}</dcterms:created>
+</cp:coreProperties>
+"#,
+        escape(&metadata.filestem),
+        escape(&metadata.author),
+        metadata.datetime.to_rfc3339_opts(chrono::SecondsFormat::Secs, false)
+    )
+}
+
+/// The body of the document: the title, the metadata table, the header, the sections and the footer.
+fn document(metadata: &Metadata) -> String {
+    let mut body = paragraph(Some("Title"), &metadata.filestem);
+
+    body.push_str("<w:tbl><w:tblPr><w:tblStyle w:val=\"TableGrid\"/><w:tblW w:w=\"0\" w:type=\"auto\"/></w:tblPr>");
+    body.push_str(&format!(
+        "<w:tblGrid><w:gridCol w:w=\"{}\"/><w:gridCol w:w=\"{}\"/></w:tblGrid>",
+        LABEL_WIDTH, VALUE_WIDTH
+    ));
+    let created = metadata.datetime.format("%Y-%m-%d %H:%M:%S").to_string();
+    let rows = [("Created", created.as_str()), ("Author", metadata.author.as_str())]
+        .into_iter()
+        .chain(metadata.extra_values.iter().map(|(label, value)| (label.as_str(), value.as_str())));
+    for (label, value) in rows {
+        body.push_str(&format!(
+            "<w:tr><w:tc>{}</w:tc><w:tc>{}</w:tc></w:tr>",
+            cell(label, LABEL_WIDTH, true),
+            cell(value, VALUE_WIDTH, false)
+        ));
+    }
+    body.push_str("</w:tbl>");
+
+    if let Some(header) = metadata.header.as_deref().filter(|header| !header.trim().is_empty()) {
+        body.push_str(&blocks(header));
+    }
+    for section in metadata.sections.iter().flatten() {
+        body.push_str(&paragraph(Some("Heading1"), &section.heading));
+        if let Some(text) = &section.body {
+            body.push_str(&blocks(text));
+        }
+    }
+    if let Some(footer) = metadata.footer.as_deref().filter(|footer| !footer.trim().is_empty()) {
+        body.push_str(&blocks(footer));
+    }
+
+    format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\n\
+        <w:document xmlns:w=\"http://schemas.openxmlformats.org/wordprocessingml/2006/main\">\
+        <w:body>{}<w:sectPr><w:pgSz w:w=\"11906\" w:h=\"16838\"/>\
+        <w:pgMar w:top=\"1440\" w:right=\"1440\" w:bottom=\"1440\" w:left=\"1440\" w:header=\"708\" w:footer=\"708\" w:gutter=\"0\"/>\
+        </w:sectPr></w:body></w:document>\n",
+        body
+    )
+}
+
+/// Convert the given text to paragraphs, bulleted lists and checklists.
+fn blocks(text: &str) -> String {
+    let mut xml = String::new();
+    for block in Block::parse(text) {
+        match block {
+            Block::Paragraph(lines) => {
+                let runs: Vec<String> = lines.iter().map(|line| run(line, false)).collect();
+                xml.push_str(&format!("<w:p>{}</w:p>", runs.join("<w:r><w:br/></w:r>")));
+            }
+            Block::List(items) => {
+                for item in items {
+                    xml.push_str(&format!(
+                        "<w:p><w:pPr><w:pStyle w:val=\"ListParagraph\"/><w:numPr><w:ilvl w:val=\"0\"/><w:numId w:val=\"1\"/></w:numPr></w:pPr>{}</w:p>",
+                        run(&item, false)
+                    ));
+                }
+            }
+            Block::Checklist(items) => {
+                for (checked, item) in items {
+                    let check = if checked { "☒" } else { "☐" };
+                    xml.push_str(&format!(
+                        "<w:p><w:pPr><w:pStyle w:val=\"ListParagraph\"/></w:pPr>{}</w:p>",
+                        run(&format!("{} {}", check, item), false)
+                    ));
+                }
+            }
+        }
+    }
+    xml
+}
+
+/// A paragraph of the given text with the given style.
+fn paragraph(style: Option<&str>, text: &str) -> String {
+    match style {
+        Some(style) => format!("<w:p><w:pPr><w:pStyle w:val=\"{}\"/></w:pPr>{}</w:p>", style, run(text, false)),
+        None => format!("<w:p>{}</w:p>", run(text, false)),
+    }
+}
+
+/// A cell of the metadata table with the given width, in bold for the labels.
+fn cell(text: &str, width: u32, bold: bool) -> String {
+    format!("<w:tcPr><w:tcW w:w=\"{}\" w:type=\"dxa\"/></w:tcPr><w:p>{}</w:p>", width, run(text, bold))
+}
+
+/// A run of the given text, keeping its leading and trailing spaces.
+fn run(text: &str, bold: bool) -> String {
+    let properties = if bold { "<w:rPr><w:b/></w:rPr>" } else { "" };
+    format!("<w:r>{}<w:t xml:space=\"preserve\">{}</w:t></w:r>", properties, escape(text))
+}
+
+/// Report an error of the zip archive as an error rendering the document.
+fn zip_error(e: zip::result::ZipError) -> Error {
+    Error::Render(format!("Error writing the docx archive: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Read;
+    use std::path::Path;
+    use quick_xml::events::Event;
+    use quick_xml::Reader;
+    use zip::ZipArchive;
+    use crate::metadata::parser;
+
+    /// Export the document in `testdata` and unzip it into its parts.
+    fn unzip(document: &str) -> Vec<(String, String)> {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("src/export/testdata").join(document);
+        let metadata = parser::parse_file(&path).unwrap();
+        let mut docx = vec![];
+        Docx.render(&metadata, &mut docx).unwrap();
+        let mut archive = ZipArchive::new(Cursor::new(docx)).unwrap();
+        (0..archive.len())
+            .map(|i| {
+                let mut file = archive.by_index(i).unwrap();
+                let mut content = String::new();
+                file.read_to_string(&mut content).unwrap();
+                (file.name().to_string(), content)
+            })
+            .collect()
+    }
+
+    /// The text of every paragraph of the given XML, failing if the XML is not well-formed.
+    fn paragraphs(xml: &str) -> Vec<String> {
+        let mut reader = Reader::from_str(xml);
+        let mut paragraphs = vec![];
+        let mut text = None;
+        loop {
+            match reader.read_event().unwrap() {
+                Event::Start(e) if e.name().as_ref() == b"w:p" => text = Some(String::new()),
+                Event::End(e) if e.name().as_ref() == b"w:p" => paragraphs.extend(text.take()),
+                Event::Text(e) => {
+                    if let Some(text) = &mut text {
+                        text.push_str(&e.unescape().unwrap());
+                    }
+                }
+                Event::Eof => break,
+                _ => (),
+            }
+        }
+        paragraphs
+    }
+
+    #[test]
+    fn test_package() {
+        let parts = unzip("weekly.md");
+        let names: Vec<&str> = parts.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(
+            names,
+            [
+                "[Content_Types].xml",
+                "_rels/.rels",
+                "docProps/core.xml",
+                "word/_rels/document.xml.rels",
+                "word/document.xml",
+                "word/styles.xml",
+                "word/numbering.xml",
+            ]
+        );
+        for (_, content) in &parts {
+            paragraphs(content);
+        }
+    }
+
+    #[test]
+    fn test_document() {
+        let parts = unzip("weekly.md");
+        let document = &parts.iter().find(|(name, _)| name == "word/document.xml").unwrap().1;
+        let paragraphs = paragraphs(document);
+        assert_eq!(paragraphs[0], "Weekly sync");
+        assert!(paragraphs.contains(&"Author".to_string()));
+        assert!(paragraphs.contains(&"☐ Write the release notes".to_string()));
+        assert!(paragraphs.contains(&"☒ Book the meeting room".to_string()));
+        assert!(paragraphs.contains(&"Agenda for the week & the <release>".to_string()));
+        assert!(document.contains("<w:pStyle w:val=\"Heading1\"/></w:pPr><w:r><w:t xml:space=\"preserve\">Action Items</w:t>"));
+        assert!(document.contains("<w:numId w:val=\"1\"/>"));
+    }
+
+    #[test]
+    fn test_document_korean() {
+        let parts = unzip("release.txt");
+        let core = &parts.iter().find(|(name, _)| name == "docProps/core.xml").unwrap().1;
+        assert!(core.contains("<dc:title>주간 회의</dc:title>"));
+        assert!(core.contains("<dc:creator>김민수</dc:creator>"));
+        let document = &parts.iter().find(|(name, _)| name == "word/document.xml").unwrap().1;
+        let paragraphs = paragraphs(document);
+        assert_eq!(paragraphs[0], "주간 회의");
+        assert!(paragraphs.contains(&"☐ ".to_string()));
+        assert_eq!(paragraphs.last().unwrap(), "footer line");
+    }
+
+    #[test]
+    fn test_document_with_control_characters() {
+        let metadata = Metadata {
+            filestem: "Weekly\x0Bsync".to_string(),
+            author: "John Doe".to_string(),
+            header: Some("page one\x0Cpage two".to_string()),
+            ..Default::default()
+        };
+        let mut docx = vec![];
+        Docx.render(&metadata, &mut docx).unwrap();
+        let mut archive = ZipArchive::new(Cursor::new(docx)).unwrap();
+        let mut document = String::new();
+        archive.by_name("word/document.xml").unwrap().read_to_string(&mut document).unwrap();
+        assert!(!document.contains(['\x0B', '\x0C']));
+        let paragraphs = paragraphs(&document);
+        assert_eq!(paragraphs[0], "Weekly\u{FFFD}sync");
+        assert!(paragraphs.contains(&"page one\u{FFFD}page two".to_string()));
+    }
+}
@@ -0,0 +1,198 @@
+//! Export to a standalone HTML page.
+//!
+//! The page has a table of the metadata, a `<section>` for every section of the document
+//! and an embedded stylesheet that also lays the page out for printing.
+//! The text of the header, the sections and the footer is converted to the [`Block`]s of HTML.
+//!
+
+use std::io::Write;
+use crate::error::Result;
+use crate::export::{escape, Block};
+use crate::metadata::metadata::Metadata;
+use crate::metadata::renderer::Renderer;
+
+/// The stylesheet embedded in the page.
+const STYLESHEET: &str = r#"body {
+  margin: 0;
+  color: #222;
+  background: #fff;
+  font: 16px/1.6 -apple-system, "Segoe UI", "Noto Sans KR", "Apple SD Gothic Neo", sans-serif;
+}
+article {
+  max-width: 48rem;
+  margin: 2rem auto;
+  padding: 0 1.5rem;
+}
+h1 {
+  margin-bottom: 0.5rem;
+  font-size: 2rem;
+}
+h2 {
+  margin-top: 2rem;
+  padding-bottom: 0.25rem;
+  border-bottom: 1px solid #ddd;
+  font-size: 1.4rem;
+}
+table.metadata {
+  border-collapse: collapse;
+  margin: 1rem 0 2rem;
+}
+table.metadata th,
+table.metadata td {
+  padding: 0.25rem 1rem 0.25rem 0;
+  text-align: left;
+  vertical-align: top;
+}
+table.metadata th {
+  color: #666;
+  font-weight: normal;
+}
+ul.checklist {
+  padding-left: 0;
+  list-style: none;
+}
+ul.checklist input {
+  margin-right: 0.5rem;
+}
+footer {
+  margin-top: 3rem;
+  color: #666;
+}
+@media print {
+  body {
+    font-size: 11pt;
+  }
+  article {
+    max-width: none;
+    margin: 0;
+    padding: 0;
+  }
+  section {
+    break-inside: avoid;
+  }
+  h2 {
+    break-after: avoid;
+  }
+}
+"#;
+
+/// A standalone HTML page.
+pub struct Html;
+
+impl Renderer for Html {
+    fn render(&self, metadata: &Metadata, writer: &mut dyn Write) -> Result<()> {
+        let title = escape(&metadata.filestem);
+        let mut html = String::new();
+        html.push_str("<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n");
+        html.push_str("<meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">\n");
+        html.push_str(&format!("<meta name=\"author\" content=\"{}\">\n", escape(&metadata.author)));
+        html.push_str(&format!("<title>{}</title>\n<style>\n{}</style>\n</head>\n", title, STYLESHEET));
+        html.push_str(&format!("<body>\n<article>\n<h1>{}</h1>\n", title));
+
+        html.push_str("<table class=\"metadata\">\n");
+        let created = metadata.datetime.format("%Y-%m-%d %H:%M:%S").to_string();
+        let rows = [("Created", created.as_str()), ("Author", metadata.author.as_str())]
+            .into_iter()
+            .chain(metadata.extra_values.iter().map(|(label, value)| (label.as_str(), value.as_str())));
+        for (label, value) in rows {
+            html.push_str(&format!("<tr><th>{}</th><td>{}</td></tr>\n", escape(label), escape(value)));
+        }
+        html.push_str("</table>\n");
+
+        if let Some(header) = metadata.header.as_deref().filter(|header| !header.trim().is_empty()) {
+            html.push_str(&format!("<header>\n{}</header>\n", blocks(header)));
+        }
+        for section in metadata.sections.iter().flatten() {
+            html.push_str(&format!("<section>\n<h2>{}</h2>\n", escape(&section.heading)));
+            if let Some(body) = &section.body {
+                html.push_str(&blocks(body));
+            }
+            html.push_str("</section>\n");
+        }
+        if let Some(footer) = metadata.footer.as_deref().filter(|footer| !footer.trim().is_empty()) {
+            html.push_str(&format!("<footer>\n{}</footer>\n", blocks(footer)));
+        }
+        html.push_str("</article>\n</body>\n</html>\n");
+
+        writer.write_all(html.as_bytes())?;
+        writer.flush()?;
+        Ok(())
+    }
+}
+
+/// Convert the given text to paragraphs, lists and checklists.
+fn blocks(text: &str) -> String {
+    let mut html = String::new();
+    for block in Block::parse(text) {
+        match block {
+            Block::Paragraph(lines) => {
+                let lines: Vec<String> = lines.iter().map(|line| escape(line)).collect();
+                html.push_str(&format!("<p>{}</p>\n", lines.join("<br>\n")));
+            }
+            Block::List(items) => {
+                html.push_str("<ul>\n");
+                for item in items {
+                    html.push_str(&format!("<li>{}</li>\n", escape(&item)));
+                }
+                html.push_str("</ul>\n");
+            }
+            Block::Checklist(items) => {
+                html.push_str("<ul class=\"checklist\">\n");
+                for (checked, item) in items {
+                    html.push_str(&match checked {
+                        true => format!("<li><input type=\"checkbox\" checked disabled>{}</li>\n", escape(&item)),
+                        false => format!("<li><input type=\"checkbox\" disabled>{}</li>\n", escape(&item)),
+                    });
+                }
+                html.push_str("</ul>\n");
+            }
+        }
+    }
+    html
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::path::Path;
+    use crate::metadata::parser;
+
+    /// Compare the export of the document in `testdata` with its golden file.
+    /// Set `MOMI_UPDATE_GOLDEN` to write the golden file instead.
+    fn assert_golden(document: &str, golden: &str) {
+        let testdata = Path::new(env!("CARGO_MANIFEST_DIR")).join("src/export/testdata");
+        let path = testdata.join(document);
+        let metadata = parser::parse_file(&path).unwrap();
+        let mut html = vec![];
+        Html.render(&metadata, &mut html).unwrap();
+        let html = String::from_utf8(html).unwrap();
+        if std::env::var_os("MOMI_UPDATE_GOLDEN").is_some() {
+            fs::write(testdata.join(golden), &html).unwrap();
+        }
+        assert_eq!(html, fs::read_to_string(testdata.join(golden)).unwrap());
+    }
+
+    #[test]
+    fn test_golden_markdown() {
+        assert_golden("weekly.md", "weekly.html");
+    }
+
+    #[test]
+    fn test_golden_text() {
+        assert_golden("release.txt", "release.html");
+    }
+
+    #[test]
+    fn test_blocks() {
+        assert_eq!(
+            blocks("Agenda <draft>\nsecond line\n\n- one\n* two\n- [ ] todo\n- [x] done\n- [ ]"),
+            "<p>Agenda &lt;draft&gt;<br>\nsecond line</p>\n\
+            <ul>\n<li>one</li>\n<li>two</li>\n</ul>\n\
+            <ul class=\"checklist\">\n\
+            <li><input type=\"checkbox\" disabled>todo</li>\n\
+            <li><input type=\"checkbox\" checked disabled>done</li>\n\
+            <li><input type=\"checkbox\" disabled></li>\n</ul>\n"
+        );
+    }
+}
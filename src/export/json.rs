@@ -0,0 +1,237 @@
+//! Export to JSON, for dashboards and other tools that read the minutes as data.
+//!
+//! `json` writes a document as a pretty-printed [`Minutes`] object and `ndjson` writes it as a single line,
+//! so the minutes of many documents can be streamed one per line.
+//!
+//! The schema is versioned with [`SCHEMA_VERSION`], which is increased when a field is removed or changes its meaning.
+//! Adding a field does not change the version, so readers should ignore the fields they do not know.
+//!
+//! ```json
+//! {
+//!   "schema_version": 1,
+//!   "title": "Weekly sync",
+//!   "created": "2024-05-02T09:30:00+09:00",
+//!   "author": "John Doe",
+//!   "extension": "md",
+//!   "fields": [{ "name": "location", "value": "Seoul" }],
+//!   "header": "Agenda for the week",
+//!   "sections": [
+//!     { "heading": "Action Items", "body": "- [ ] Write the release notes" },
+//!     { "heading": "Decisions Made", "body": "- Ship on Friday" }
+//!   ],
+//!   "action_items": [{ "text": "Write the release notes", "done": false, "section": "Action Items" }],
+//!   "decisions": ["Ship on Friday"],
+//!   "footer": null
+//! }
+//! ```
+//!
+//! - `created` is in RFC 3339 with the offset of the time zone the document was read in.
+//! - `action_items` are the `- [ ] item` and `- [x] item` lines of every section.
+//! - `decisions` are the list items, `- item` or `* item`, of the sections whose heading has one of [`DECISION_HEADINGS`] in it,
+//!   such as "Decisions Made" or "결정 사항". The paragraphs of those sections are explanations, not decisions,
+//!   so they are left out. The decisions of a section with another heading are only in `sections`.
+//!
+
+use std::io::Write;
+use chrono::SecondsFormat;
+use serde_derive::{Deserialize, Serialize};
+use crate::error::{Error, Result};
+use crate::export::Block;
+use crate::metadata::metadata::Metadata;
+use crate::metadata::renderer::Renderer;
+
+/// The version of the schema of [`Minutes`].
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// The words that mark a section of decisions when its heading has one of them, ignoring case.
+pub const DECISION_HEADINGS: [&str; 2] = ["decision", "결정"];
+
+/// The minutes of a document as data.
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct Minutes {
+    /// The version of the schema, [`SCHEMA_VERSION`] when written by this version of momi.
+    pub schema_version: u32,
+    pub title: String,
+    /// The date and time the document was created, in RFC 3339.
+    pub created: String,
+    pub author: String,
+    /// The extension of the document, without the dot.
+    pub extension: Option<String>,
+    /// The extra metadata, in the order of the document.
+    pub fields: Vec<Field>,
+    pub header: Option<String>,
+    pub sections: Vec<Section>,
+    pub action_items: Vec<ActionItem>,
+    pub decisions: Vec<String>,
+    pub footer: Option<String>,
+}
+
+/// An extra metadata of the document.
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct Field {
+    pub name: String,
+    pub value: String,
+}
+
+/// A section of the document, with its body as written.
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct Section {
+    pub heading: String,
+    pub body: Option<String>,
+}
+
+/// An item of a checklist in a section.
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct ActionItem {
+    pub text: String,
+    /// Whether the item is checked, `- [x] item`.
+    pub done: bool,
+    /// The heading of the section the item is in.
+    pub section: String,
+}
+
+impl From<&Metadata> for Minutes {
+    fn from(metadata: &Metadata) -> Self {
+        let sections = metadata.sections.iter().flatten();
+        let mut action_items = vec![];
+        let mut decisions = vec![];
+        for section in sections.clone() {
+            let heading = section.heading.to_lowercase();
+            let is_decisions = DECISION_HEADINGS.iter().any(|word| heading.contains(word));
+            for block in Block::parse(section.body.as_deref().unwrap_or_default()) {
+                match block {
+                    Block::Checklist(items) => action_items.extend(items.into_iter().filter(|(_, text)| !text.is_empty()).map(
+                        |(done, text)| ActionItem { text, done, section: section.heading.clone() },
+                    )),
+                    Block::List(items) if is_decisions => decisions.extend(items),
+                    _ => (),
+                }
+            }
+        }
+
+        Minutes {
+            schema_version: SCHEMA_VERSION,
+            title: metadata.filestem.clone(),
+            created: metadata.datetime.to_rfc3339_opts(SecondsFormat::Secs, true),
+            author: metadata.author.clone(),
+            extension: metadata.extension.clone(),
+            fields: metadata
+                .extra_values
+                .iter()
+                .map(|(name, value)| Field { name: name.clone(), value: value.clone() })
+                .collect(),
+            header: metadata.header.clone(),
+            sections: sections
+                .map(|section| Section { heading: section.heading.clone(), body: section.body.clone() })
+                .collect(),
+            action_items,
+            decisions,
+            footer: metadata.footer.clone(),
+        }
+    }
+}
+
+/// A pretty-printed JSON object.
+pub struct Json;
+
+impl Renderer for Json {
+    fn render(&self, metadata: &Metadata, writer: &mut dyn Write) -> Result<()> {
+        serde_json::to_writer_pretty(&mut *writer, &Minutes::from(metadata)).map_err(json_error)?;
+        writer.write_all(b"\n")?;
+        writer.flush()?;
+        Ok(())
+    }
+}
+
+/// A JSON object on a single line, followed by a newline.
+pub struct Ndjson;
+
+impl Renderer for Ndjson {
+    fn render(&self, metadata: &Metadata, writer: &mut dyn Write) -> Result<()> {
+        serde_json::to_writer(&mut *writer, &Minutes::from(metadata)).map_err(json_error)?;
+        writer.write_all(b"\n")?;
+        writer.flush()?;
+        Ok(())
+    }
+}
+
+/// Report an error writing the JSON, keeping the errors of the writer as I/O errors.
+fn json_error(e: serde_json::Error) -> Error {
+    match e.io_error_kind() {
+        Some(kind) => Error::Io(std::io::Error::new(kind, e.to_string())),
+        None => Error::Render(format!("Error writing JSON: {}", e)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+    use crate::metadata::parser;
+
+    fn parse(document: &str) -> Metadata {
+        parser::parse_file(Path::new(env!("CARGO_MANIFEST_DIR")).join("src/export/testdata").join(document)).unwrap()
+    }
+
+    #[test]
+    fn test_minutes() {
+        let metadata = parse("weekly.md");
+        let minutes = Minutes::from(&metadata);
+        assert_eq!(minutes.schema_version, SCHEMA_VERSION);
+        assert_eq!(minutes.title, "Weekly sync");
+        assert!(minutes.created.starts_with("2024-05-02T09:30:00"));
+        assert_eq!(chrono::DateTime::parse_from_rfc3339(&minutes.created).unwrap(), metadata.datetime);
+        assert_eq!(minutes.extension.as_deref(), Some("md"));
+        assert_eq!(minutes.fields[1], Field { name: "attendees".to_string(), value: "Jane Doe, Kim Minsu".to_string() });
+        assert_eq!(minutes.sections.len(), 3);
+        assert_eq!(
+            minutes.action_items,
+            [
+                ActionItem { text: "Write the release notes".to_string(), done: false, section: "Action Items".to_string() },
+                ActionItem { text: "Book the meeting room".to_string(), done: true, section: "Action Items".to_string() },
+            ]
+        );
+        assert_eq!(minutes.decisions, ["Freeze the API", "Tag v1.2"]);
+        assert_eq!(minutes.footer.as_deref(), Some("Next meeting on Monday"));
+    }
+
+    #[test]
+    fn test_decisions_korean() {
+        let metadata = Metadata {
+            sections: Some(vec![crate::config::section::Section {
+                body: Some("금요일에 배포하기로 했다.\n\n- API 동결\n* 베타 유지".to_string()),
+                ..crate::config::section::Section::new("결정 사항")
+            }]),
+            ..Default::default()
+        };
+        assert_eq!(Minutes::from(&metadata).decisions, ["API 동결", "베타 유지"]);
+    }
+
+    #[test]
+    fn test_render_json() {
+        let metadata = parse("release.txt");
+        let mut json = vec![];
+        Json.render(&metadata, &mut json).unwrap();
+        let json = String::from_utf8(json).unwrap();
+        assert!(json.starts_with("{\n  \"schema_version\": 1,\n  \"title\": \"주간 회의\","));
+        let minutes: Minutes = serde_json::from_str(&json).unwrap();
+        assert_eq!(minutes, Minutes::from(&metadata));
+        // An empty checklist item is a placeholder, not an action item.
+        assert!(minutes.action_items.is_empty());
+    }
+
+    #[test]
+    fn test_render_ndjson() {
+        let mut ndjson = vec![];
+        for document in ["weekly.md", "release.txt"] {
+            Ndjson.render(&parse(document), &mut ndjson).unwrap();
+        }
+        let ndjson = String::from_utf8(ndjson).unwrap();
+        let titles: Vec<String> = ndjson
+            .lines()
+            .map(|line| serde_json::from_str::<Minutes>(line).unwrap().title)
+            .collect();
+        assert_eq!(titles, ["Weekly sync", "주간 회의"]);
+        assert!(ndjson.ends_with("}\n"));
+    }
+}
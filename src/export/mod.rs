@@ -0,0 +1,170 @@
+//! Export of existing documents to formats for readers outside the terminal, such as HTML, Word and PDF,
+//! and to JSON for tools that read the minutes as data.
+//!
+//! A document is read back with the [`parser`](crate::metadata::parser)
+//! and written with the [`Renderer`] of the export format.
+//! The text of the header, the sections and the footer is converted line by line to [`Block`]s:
+//!
+//! - `- [ ] item` and `- [x] item` lines are a checklist, such as the action items
+//! - `- item` and `* item` lines are a list
+//! - The other lines are paragraphs, separated by blank lines
+//!
+
+use clap::ValueEnum;
+use crate::metadata::renderer::Renderer;
+
+pub mod docx;
+pub mod html;
+pub mod json;
+#[cfg(feature = "pdf")]
+pub mod pdf;
+
+/// The format a document is exported to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ExportFormat {
+    /// A standalone HTML page with an embedded stylesheet
+    Html,
+    /// A Word document
+    Docx,
+    /// A PDF document with the fonts embedded, for archiving
+    #[cfg(feature = "pdf")]
+    Pdf,
+    /// A JSON object with a versioned schema, for dashboards and other tools
+    Json,
+    /// A JSON object per line, one for every document
+    Ndjson,
+}
+
+impl ExportFormat {
+    /// The extension of the exported file.
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Html => "html",
+            ExportFormat::Docx => "docx",
+            #[cfg(feature = "pdf")]
+            ExportFormat::Pdf => "pdf",
+            ExportFormat::Json => "json",
+            ExportFormat::Ndjson => "ndjson",
+        }
+    }
+
+    /// The renderer writing this format.
+    pub fn renderer(&self) -> Box<dyn Renderer> {
+        match self {
+            ExportFormat::Html => Box::new(html::Html),
+            ExportFormat::Docx => Box::new(docx::Docx),
+            #[cfg(feature = "pdf")]
+            ExportFormat::Pdf => Box::new(pdf::Pdf),
+            ExportFormat::Json => Box::new(json::Json),
+            ExportFormat::Ndjson => Box::new(json::Ndjson),
+        }
+    }
+}
+
+/// A block of lines of the same kind in the text of a document.
+#[derive(Debug, PartialEq)]
+pub enum Block {
+    /// The lines of a paragraph.
+    Paragraph(Vec<String>),
+    /// The items of a list.
+    List(Vec<String>),
+    /// The items of a checklist and whether they are checked.
+    Checklist(Vec<(bool, String)>),
+}
+
+impl Block {
+    /// Split the given text into blocks.
+    pub fn parse(text: &str) -> Vec<Block> {
+        let mut blocks = vec![];
+        let mut current: Option<Block> = None;
+        for line in text.lines().map(str::trim_end) {
+            let next = match (task(line), line.strip_prefix("- ").or_else(|| line.strip_prefix("* "))) {
+                _ if line.trim().is_empty() => None,
+                (Some((checked, item)), _) => match &mut current {
+                    Some(Block::Checklist(items)) => {
+                        items.push((checked, item.to_string()));
+                        continue;
+                    }
+                    _ => Some(Block::Checklist(vec![(checked, item.to_string())])),
+                },
+                (None, Some(item)) => match &mut current {
+                    Some(Block::List(items)) => {
+                        items.push(item.trim().to_string());
+                        continue;
+                    }
+                    _ => Some(Block::List(vec![item.trim().to_string()])),
+                },
+                (None, None) => match &mut current {
+                    Some(Block::Paragraph(lines)) => {
+                        lines.push(line.trim().to_string());
+                        continue;
+                    }
+                    _ => Some(Block::Paragraph(vec![line.trim().to_string()])),
+                },
+            };
+            blocks.extend(current.take());
+            current = next;
+        }
+        blocks.extend(current);
+        blocks
+    }
+}
+
+/// Read a `- [ ] item` or `- [x] item` line as whether it is checked and its item.
+fn task(line: &str) -> Option<(bool, &str)> {
+    [("- [ ]", false), ("- [x]", true), ("- [X]", true)]
+        .into_iter()
+        .find_map(|(prefix, checked)| {
+            line.strip_prefix(prefix)
+                .filter(|rest| rest.is_empty() || rest.starts_with(' '))
+                .map(|rest| (checked, rest.trim()))
+        })
+}
+
+/// Escape the characters that have a meaning in HTML and XML.
+///
+/// Control characters other than tab and line breaks, such as a form feed, are not allowed in XML,
+/// so they are replaced with `U+FFFD`.
+pub fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            '\t' | '\n' | '\r' => escaped.push(c),
+            c if c.is_control() => escaped.push(char::REPLACEMENT_CHARACTER),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_blocks() {
+        assert_eq!(
+            Block::parse("Agenda\nsecond line\n\n- one\n* two\n- [ ] todo\n- [x] done\n- [ ]"),
+            vec![
+                Block::Paragraph(vec!["Agenda".to_string(), "second line".to_string()]),
+                Block::List(vec!["one".to_string(), "two".to_string()]),
+                Block::Checklist(vec![
+                    (false, "todo".to_string()),
+                    (true, "done".to_string()),
+                    (false, String::new()),
+                ]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_escape() {
+        assert_eq!(escape("<a href=\"x\">Tom & Jerry's</a>"), "&lt;a href=&quot;x&quot;&gt;Tom &amp; Jerry&#39;s&lt;/a&gt;");
+        assert_eq!(escape("page\x0Cbreak\x0B\x00\tend\n"), "page\u{FFFD}break\u{FFFD}\u{FFFD}\tend\n");
+    }
+}
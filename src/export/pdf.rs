@@ -0,0 +1,559 @@
+//! Export to a PDF document for archiving.
+//!
+//! The document is laid out on A4 pages without any external tool: a title, a table of the metadata,
+//! a heading for every section and the footer, with the page number at the bottom of every page.
+//! Lists are bulleted and checklists, such as the action items, are tables with a `☐` or `☒` column.
+//!
+//! The text is set in the bundled DejaVu Sans, with NanumBarunGothic for Hangul,
+//! and only the glyphs used by the document are embedded.
+//! A character that neither font has is an [`Error::Render`], and whitespace without a glyph is drawn as a space.
+//! The fonts and their licenses are in `src/export/fonts`.
+//!
+//! This module is built with the default `pdf` feature.
+//!
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::io::Write;
+use chrono::{Datelike, Timelike};
+use flate2::write::ZlibEncoder;
+use flate2::Compression;
+use pdf_writer::types::{CidFontType, FontFlags, SystemInfo, TextRenderingMode, UnicodeCmap};
+use pdf_writer::{Content, Date, Filter, Finish, Name, Rect, Ref, Str, TextStr};
+use subsetter::Profile;
+use ttf_parser::{Face, GlyphId};
+use crate::error::{Error, Result};
+use crate::export::Block;
+use crate::metadata::metadata::Metadata;
+use crate::metadata::renderer::Renderer;
+
+const DEJAVU_SANS: &[u8] = include_bytes!("fonts/DejaVuSans.ttf");
+const NANUM_BARUN_GOTHIC: &[u8] = include_bytes!("fonts/NanumBarunGothic.ttf");
+
+/// The size of an A4 page in points.
+const PAGE_WIDTH: f32 = 595.0;
+const PAGE_HEIGHT: f32 = 842.0;
+/// The margin around the text, 2 cm.
+const MARGIN: f32 = 56.7;
+/// The width of the text between the margins.
+const TEXT_WIDTH: f32 = PAGE_WIDTH - 2.0 * MARGIN;
+/// The space between the border of a table cell and its text.
+const CELL_PADDING: f32 = 4.0;
+/// The width of the label column of the metadata table and of the check column of a checklist.
+const LABEL_WIDTH: f32 = 120.0;
+const CHECK_WIDTH: f32 = 40.0;
+/// The indent of the items of a list.
+const LIST_INDENT: f32 = 18.0;
+
+/// Identity ordering of the glyphs, as the text is written with glyph ids.
+const SYSTEM_INFO: SystemInfo = SystemInfo { registry: Str(b"Adobe"), ordering: Str(b"Identity"), supplement: 0 };
+
+/// The size and weight of a line of text. Bold text is drawn with an outline, so it also works for Hangul.
+#[derive(Debug, Clone, Copy)]
+struct Style {
+    size: f32,
+    bold: bool,
+}
+
+const TITLE: Style = Style { size: 20.0, bold: true };
+const HEADING: Style = Style { size: 14.0, bold: true };
+const TEXT: Style = Style { size: 10.5, bold: false };
+const LABEL: Style = Style { size: 10.5, bold: true };
+const PAGE_NUMBER: Style = Style { size: 8.0, bold: false };
+
+impl Style {
+    fn line_height(&self) -> f32 {
+        self.size * 1.45
+    }
+
+    /// The baseline of a line starting at the given height, with the text centered in the line.
+    fn baseline(&self, top: f32) -> f32 {
+        top - (self.line_height() + self.size * 0.7) / 2.0
+    }
+}
+
+/// A PDF document.
+pub struct Pdf;
+
+impl Renderer for Pdf {
+    fn render(&self, metadata: &Metadata, writer: &mut dyn Write) -> Result<()> {
+        let mut layout = Layout::new()?;
+        layout.document(metadata);
+        writer.write_all(&layout.finish(metadata)?)?;
+        writer.flush()?;
+        Ok(())
+    }
+}
+
+/// A bundled font and the glyphs used from it.
+struct Font {
+    data: &'static [u8],
+    face: Face<'static>,
+    /// The name of the font in the resources of the pages.
+    resource: Name<'static>,
+    /// The PostScript name, with the tag of a subset.
+    base_font: Name<'static>,
+    /// The characters of the used glyphs, for copying the text out of the PDF.
+    glyphs: BTreeMap<u16, char>,
+}
+
+impl Font {
+    fn new(data: &'static [u8], resource: &'static [u8], base_font: &'static [u8]) -> Result<Font> {
+        let face = Face::parse(data, 0).map_err(|e| Error::Render(format!("Error reading the bundled font: {}", e)))?;
+        Ok(Font { data, face, resource: Name(resource), base_font: Name(base_font), glyphs: BTreeMap::new() })
+    }
+
+    /// The given length in font units, scaled to the given size.
+    fn scale(&self, units: f32, size: f32) -> f32 {
+        units * size / self.face.units_per_em() as f32
+    }
+
+    fn advance(&self, glyph: u16, size: f32) -> f32 {
+        self.scale(self.face.glyph_hor_advance(GlyphId(glyph)).unwrap_or(0) as f32, size)
+    }
+}
+
+/// The pages of the document, laid out from the top down.
+struct Layout {
+    fonts: Vec<Font>,
+    pages: Vec<Content>,
+    /// The top of the next line on the last page.
+    y: f32,
+    /// The characters that no font has.
+    missing: BTreeSet<char>,
+}
+
+impl Layout {
+    fn new() -> Result<Layout> {
+        Ok(Layout {
+            fonts: vec![
+                Font::new(DEJAVU_SANS, b"F1", b"MOMIDV+DejaVuSans")?,
+                Font::new(NANUM_BARUN_GOTHIC, b"F2", b"MOMINB+NanumBarunGothic")?,
+            ],
+            pages: vec![Content::new()],
+            y: PAGE_HEIGHT - MARGIN,
+            missing: BTreeSet::new(),
+        })
+    }
+
+    /// Lay out the title, the metadata table, the header, the sections and the footer.
+    fn document(&mut self, metadata: &Metadata) {
+        for line in self.wrap(&metadata.filestem, TITLE, TEXT_WIDTH) {
+            self.line(MARGIN, &line, TITLE);
+        }
+        self.y -= 6.0;
+
+        let mut rows = vec![
+            ("Created".to_string(), metadata.datetime.format("%Y-%m-%d %H:%M:%S").to_string()),
+            ("Author".to_string(), metadata.author.clone()),
+        ];
+        rows.extend(metadata.extra_values.iter().cloned());
+        for (label, value) in &rows {
+            self.row(label, value, LABEL_WIDTH, LABEL, false);
+        }
+        self.y -= 12.0;
+
+        if let Some(header) = metadata.header.as_deref().filter(|header| !header.trim().is_empty()) {
+            self.blocks(header);
+        }
+        for section in metadata.sections.iter().flatten() {
+            self.y -= 8.0;
+            // Keep the heading on the same page as the first lines of the section.
+            self.ensure(HEADING.line_height() + 2.0 * TEXT.line_height());
+            for line in self.wrap(&section.heading, HEADING, TEXT_WIDTH) {
+                self.line(MARGIN, &line, HEADING);
+            }
+            let y = self.y;
+            self.page().set_stroke_gray(0.8).set_line_width(0.75).move_to(MARGIN, y).line_to(PAGE_WIDTH - MARGIN, y).stroke();
+            self.y -= 6.0;
+            if let Some(body) = &section.body {
+                self.blocks(body);
+            }
+        }
+        if let Some(footer) = metadata.footer.as_deref().filter(|footer| !footer.trim().is_empty()) {
+            self.y -= 12.0;
+            self.blocks(footer);
+        }
+    }
+
+    /// Lay out paragraphs, bulleted lists and checklists.
+    fn blocks(&mut self, text: &str) {
+        for block in Block::parse(text) {
+            match block {
+                Block::Paragraph(lines) => {
+                    let lines: Vec<String> = lines.iter().flat_map(|line| self.wrap(line, TEXT, TEXT_WIDTH)).collect();
+                    for line in lines {
+                        self.line(MARGIN, &line, TEXT);
+                    }
+                }
+                Block::List(items) => {
+                    for item in items {
+                        for (i, line) in self.wrap(&item, TEXT, TEXT_WIDTH - LIST_INDENT).iter().enumerate() {
+                            if i == 0 {
+                                self.ensure(TEXT.line_height());
+                                let baseline = TEXT.baseline(self.y);
+                                self.show(MARGIN + 6.0, baseline, "•", TEXT);
+                            }
+                            self.line(MARGIN + LIST_INDENT, line, TEXT);
+                        }
+                    }
+                }
+                Block::Checklist(items) => {
+                    self.row("Done", "Item", CHECK_WIDTH, LABEL, true);
+                    for (checked, item) in items {
+                        self.row(if checked { "☒" } else { "☐" }, &item, CHECK_WIDTH, TEXT, false);
+                    }
+                }
+            }
+            self.y -= TEXT.line_height() / 2.0;
+        }
+    }
+
+    /// Lay out a line of text at the given left edge.
+    fn line(&mut self, x: f32, text: &str, style: Style) {
+        self.ensure(style.line_height());
+        let baseline = style.baseline(self.y);
+        self.show(x, baseline, text, style);
+        self.y -= style.line_height();
+    }
+
+    /// Lay out a row of a table of two columns, with the given width of the first column
+    /// and the style of its text. A header row is shaded and its text is bold.
+    fn row(&mut self, first: &str, second: &str, first_width: f32, first_style: Style, header: bool) {
+        let second_style = if header { LABEL } else { TEXT };
+        let first_lines = self.wrap(first, first_style, first_width - 2.0 * CELL_PADDING);
+        let second_lines = self.wrap(second, second_style, TEXT_WIDTH - first_width - 2.0 * CELL_PADDING);
+        let height = first_lines.len().max(second_lines.len()) as f32 * TEXT.line_height() + 2.0 * CELL_PADDING;
+        self.ensure(height);
+
+        let top = self.y;
+        let page = self.page();
+        if header {
+            page.set_fill_gray(0.93).rect(MARGIN, top - height, TEXT_WIDTH, height).fill_nonzero().set_fill_gray(0.0);
+        }
+        page.set_stroke_gray(0.7).set_line_width(0.5);
+        page.rect(MARGIN, top - height, first_width, height);
+        page.rect(MARGIN + first_width, top - height, TEXT_WIDTH - first_width, height);
+        page.stroke();
+
+        let cells = [(MARGIN, first_lines, first_style), (MARGIN + first_width, second_lines, second_style)];
+        for (x, lines, style) in cells {
+            for (i, line) in lines.iter().enumerate() {
+                let baseline = style.baseline(top - CELL_PADDING - i as f32 * TEXT.line_height());
+                self.show(x + CELL_PADDING, baseline, line, style);
+            }
+        }
+        self.y = top - height;
+    }
+
+    /// Start a new page if the given height does not fit on the last page.
+    fn ensure(&mut self, height: f32) {
+        if self.y - height < MARGIN && self.y < PAGE_HEIGHT - MARGIN {
+            self.pages.push(Content::new());
+            self.y = PAGE_HEIGHT - MARGIN;
+        }
+    }
+
+    fn page(&mut self) -> &mut Content {
+        self.pages.last_mut().expect("a document has at least one page")
+    }
+
+    /// The font and the glyph of the given character: DejaVu Sans, or the first font that has it.
+    /// Whitespace that no font has, such as a tab, is a space.
+    fn glyph(&self, c: char) -> Option<(usize, u16)> {
+        self.fonts
+            .iter()
+            .enumerate()
+            .find_map(|(i, font)| font.face.glyph_index(c).map(|glyph| (i, glyph.0)))
+            .or_else(|| match c.is_whitespace() && c != ' ' {
+                true => self.glyph(' '),
+                false => None,
+            })
+    }
+
+    /// The width of the given text.
+    fn width(&self, text: &str, style: Style) -> f32 {
+        text.chars()
+            .filter_map(|c| self.glyph(c))
+            .map(|(font, glyph)| self.fonts[font].advance(glyph, style.size))
+            .sum()
+    }
+
+    /// Break the given text into lines of the given width, between words,
+    /// or between characters for a word longer than a line.
+    fn wrap(&self, text: &str, style: Style, width: f32) -> Vec<String> {
+        let mut lines = vec![];
+        let mut line = String::new();
+        for word in text.split(' ') {
+            let candidate = if line.is_empty() { word.to_string() } else { format!("{} {}", line, word) };
+            if self.width(&candidate, style) <= width {
+                line = candidate;
+                continue;
+            }
+            if !line.is_empty() {
+                lines.push(std::mem::take(&mut line));
+            }
+            for c in word.chars() {
+                if !line.is_empty() && self.width(&format!("{}{}", line, c), style) > width {
+                    lines.push(std::mem::take(&mut line));
+                }
+                line.push(c);
+            }
+        }
+        lines.push(line);
+        lines
+    }
+
+    /// Draw the given text on the last page, switching the font for the characters it does not have.
+    fn show(&mut self, x: f32, baseline: f32, text: &str, style: Style) {
+        let page = self.pages.len() - 1;
+        self.show_on(page, x, baseline, text, style);
+    }
+
+    fn show_on(&mut self, page: usize, x: f32, baseline: f32, text: &str, style: Style) {
+        let mut runs: Vec<(usize, Vec<u8>)> = vec![];
+        for c in text.chars() {
+            let Some((font, glyph)) = self.glyph(c) else {
+                self.missing.insert(c);
+                continue;
+            };
+            self.fonts[font].glyphs.entry(glyph).or_insert(c);
+            match runs.last_mut() {
+                Some((last, encoded)) if *last == font => encoded.extend(glyph.to_be_bytes()),
+                _ => runs.push((font, glyph.to_be_bytes().to_vec())),
+            }
+        }
+
+        let content = &mut self.pages[page];
+        match style.bold {
+            true => content.set_text_rendering_mode(TextRenderingMode::FillStroke).set_stroke_gray(0.0).set_line_width(style.size * 0.04),
+            false => content.set_text_rendering_mode(TextRenderingMode::Fill),
+        };
+        content.begin_text().next_line(x, baseline);
+        for (font, encoded) in runs {
+            content.set_font(self.fonts[font].resource, style.size).show(Str(&encoded));
+        }
+        content.end_text();
+    }
+
+    /// Number the pages and write the PDF with the used glyphs of the fonts embedded.
+    fn finish(mut self, metadata: &Metadata) -> Result<Vec<u8>> {
+        if !self.missing.is_empty() {
+            let missing: Vec<String> = self.missing.iter().map(|c| format!("\"{}\" (U+{:04X})", c, *c as u32)).collect();
+            return Err(Error::Render(format!("The bundled fonts have no glyph for {}", missing.join(", "))));
+        }
+        let count = self.pages.len();
+        for page in 0..count {
+            let number = format!("{} / {}", page + 1, count);
+            let x = (PAGE_WIDTH - self.width(&number, PAGE_NUMBER)) / 2.0;
+            self.show_on(page, x, MARGIN / 2.0, &number, PAGE_NUMBER);
+        }
+
+        let mut pdf = pdf_writer::Pdf::new();
+        let mut next_id = Ref::new(1);
+        let catalog_id = next_id.bump();
+        let page_tree_id = next_id.bump();
+        let info_id = next_id.bump();
+        let page_ids: Vec<Ref> = (0..count).map(|_| next_id.bump()).collect();
+        let fonts: Vec<(&Font, Ref)> = self
+            .fonts
+            .iter()
+            .filter(|font| !font.glyphs.is_empty())
+            .map(|font| (font, next_id.bump()))
+            .collect();
+
+        pdf.catalog(catalog_id).pages(page_tree_id);
+        pdf.pages(page_tree_id).kids(page_ids.iter().copied()).count(count as i32);
+        for (page_id, content) in page_ids.into_iter().zip(self.pages) {
+            let content_id = next_id.bump();
+            let mut page = pdf.page(page_id);
+            page.media_box(Rect::new(0.0, 0.0, PAGE_WIDTH, PAGE_HEIGHT)).parent(page_tree_id).contents(content_id);
+            let mut resources = page.resources();
+            let mut font_resources = resources.fonts();
+            for (font, font_id) in &fonts {
+                font_resources.pair(font.resource, *font_id);
+            }
+            font_resources.finish();
+            resources.finish();
+            page.finish();
+            pdf.stream(content_id, &deflate(&content.finish())?).filter(Filter::FlateDecode);
+        }
+        for (font, font_id) in fonts {
+            embed_font(&mut pdf, font, font_id, &mut next_id)?;
+        }
+
+        let datetime = metadata.datetime;
+        let offset = datetime.offset().local_minus_utc() / 60;
+        let mut info = pdf.document_info(info_id);
+        info.title(TextStr(&metadata.filestem)).author(TextStr(&metadata.author)).creator(TextStr("momi"));
+        info.creation_date(
+            Date::new(datetime.year() as u16)
+                .month(datetime.month() as u8)
+                .day(datetime.day() as u8)
+                .hour(datetime.hour() as u8)
+                .minute(datetime.minute() as u8)
+                .second(datetime.second() as u8)
+                .utc_offset_hour((offset / 60) as i8)
+                .utc_offset_minute((offset % 60).unsigned_abs() as u8),
+        );
+        info.finish();
+        Ok(pdf.finish())
+    }
+}
+
+/// Embed the used glyphs of the font as a CID font, with a map back to the characters.
+fn embed_font(pdf: &mut pdf_writer::Pdf, font: &Font, font_id: Ref, next_id: &mut Ref) -> Result<()> {
+    let cid_font_id = next_id.bump();
+    let descriptor_id = next_id.bump();
+    let file_id = next_id.bump();
+    let cmap_id = next_id.bump();
+
+    pdf.type0_font(font_id)
+        .base_font(font.base_font)
+        .encoding_predefined(Name(b"Identity-H"))
+        .descendant_font(cid_font_id)
+        .to_unicode(cmap_id);
+
+    let mut cid_font = pdf.cid_font(cid_font_id);
+    cid_font
+        .subtype(CidFontType::Type2)
+        .base_font(font.base_font)
+        .system_info(SYSTEM_INFO)
+        .font_descriptor(descriptor_id)
+        .cid_to_gid_map_predefined(Name(b"Identity"));
+    let mut widths = cid_font.widths();
+    for &glyph in font.glyphs.keys() {
+        widths.consecutive(glyph, [font.advance(glyph, 1000.0)]);
+    }
+    widths.finish();
+    cid_font.finish();
+
+    let bbox = font.face.global_bounding_box();
+    let scale = |units: i16| font.scale(units as f32, 1000.0);
+    pdf.font_descriptor(descriptor_id)
+        .name(font.base_font)
+        .flags(FontFlags::NON_SYMBOLIC)
+        .bbox(Rect::new(scale(bbox.x_min), scale(bbox.y_min), scale(bbox.x_max), scale(bbox.y_max)))
+        .italic_angle(0.0)
+        .ascent(scale(font.face.ascender()))
+        .descent(scale(font.face.descender()))
+        .cap_height(scale(font.face.capital_height().unwrap_or(font.face.ascender())))
+        .stem_v(80.0)
+        .font_file2(file_id);
+
+    let glyphs: Vec<u16> = font.glyphs.keys().copied().collect();
+    let subset = subsetter::subset(font.data, 0, Profile::pdf(&glyphs))
+        .map_err(|e| Error::Render(format!("Error embedding the font: {}", e)))?;
+    pdf.stream(file_id, &deflate(&subset)?)
+        .filter(Filter::FlateDecode)
+        .pair(Name(b"Length1"), subset.len() as i32);
+
+    let mut cmap = UnicodeCmap::new(Name(b"Custom"), SYSTEM_INFO);
+    for (&glyph, &c) in &font.glyphs {
+        cmap.pair(glyph, c);
+    }
+    pdf.stream(cmap_id, &cmap.finish());
+    Ok(())
+}
+
+/// Compress a stream of the PDF.
+fn deflate(data: &[u8]) -> Result<Vec<u8>> {
+    let mut encoder = ZlibEncoder::new(Vec::new(), Compression::best());
+    encoder.write_all(data)?;
+    Ok(encoder.finish()?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+    use lopdf::Document;
+    use crate::config::section::Section;
+    use crate::metadata::parser;
+
+    fn render(metadata: &Metadata) -> Document {
+        let mut pdf = vec![];
+        Pdf.render(metadata, &mut pdf).unwrap();
+        assert!(pdf.starts_with(b"%PDF-"));
+        Document::load_mem(&pdf).unwrap()
+    }
+
+    fn parse(document: &str) -> Metadata {
+        parser::parse_file(Path::new(env!("CARGO_MANIFEST_DIR")).join("src/export/testdata").join(document)).unwrap()
+    }
+
+    /// The names of the fonts embedded in the first page.
+    fn fonts(document: &Document) -> Vec<String> {
+        let page = document.get_pages()[&1];
+        document
+            .get_page_fonts(page)
+            .unwrap()
+            .values()
+            .map(|font| String::from_utf8_lossy(font.get(b"BaseFont").unwrap().as_name().unwrap()).to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_render() {
+        let document = render(&parse("weekly.md"));
+        assert_eq!(document.get_pages().len(), 1);
+        assert_eq!(fonts(&document), ["MOMIDV+DejaVuSans"]);
+        let text = document.extract_text(&[1]).unwrap();
+        for expected in ["Weekly sync", "Created", "Jane Doe, Kim Minsu", "Action Items", "☐", "Write the release notes", "☒", "Freeze the API", "1 / 1"] {
+            assert!(text.contains(expected), "{} is missing in {}", expected, text);
+        }
+    }
+
+    #[test]
+    fn test_render_korean() {
+        let document = render(&parse("release.txt"));
+        assert_eq!(fonts(&document), ["MOMIDV+DejaVuSans", "MOMINB+NanumBarunGothic"]);
+        let text = document.extract_text(&[1]).unwrap();
+        for expected in ["주간 회의", "김민수", "회의 안건", "footer line"] {
+            assert!(text.contains(expected), "{} is missing in {}", expected, text);
+        }
+    }
+
+    #[test]
+    fn test_page_break() {
+        let body: Vec<String> = (1..=120).map(|i| format!("- item {}", i)).collect();
+        let metadata = Metadata {
+            filestem: "Long meeting".to_string(),
+            sections: Some(vec![Section { body: Some(body.join("\n")), ..Section::new("Notes") }]),
+            ..Default::default()
+        };
+        let document = render(&metadata);
+        let pages = document.get_pages().len() as u32;
+        assert!(pages > 1);
+        let last = document.extract_text(&[pages]).unwrap();
+        assert!(last.contains("item 120"));
+        assert!(last.contains(&format!("{} / {}", pages, pages)));
+    }
+
+    #[test]
+    fn test_missing_glyph() {
+        let metadata = Metadata {
+            filestem: "Weekly\tsync".to_string(),
+            header: Some("Private \u{E000}\u{E001} use".to_string()),
+            ..Default::default()
+        };
+        let error = Pdf.render(&metadata, &mut vec![]).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Error rendering the document: The bundled fonts have no glyph for \"\u{E000}\" (U+E000), \"\u{E001}\" (U+E001)"
+        );
+        render(&Metadata { header: None, ..metadata });
+    }
+
+    #[test]
+    fn test_wrap() {
+        let layout = Layout::new().unwrap();
+        let width = layout.width("Agenda for the", TEXT);
+        assert_eq!(layout.wrap("Agenda for the week", TEXT, width), ["Agenda for the", "week"]);
+        assert_eq!(layout.wrap("", TEXT, width), [""]);
+        let word = "가".repeat(40);
+        let lines = layout.wrap(&word, TEXT, width);
+        assert!(lines.len() > 1);
+        assert!(lines.iter().all(|line| layout.width(line, TEXT) <= width));
+        assert_eq!(lines.concat(), word);
+    }
+}
@@ -14,21 +14,54 @@
 //!
 //! ```bash
 //! momi.exe [options] <filename>
+//! momi.exe <command> [options]
 //! ```
 //!
 //! ### Mac and Linux
 //!
 //! ```bash
 //! ./momi [options] <filename>
+//! ./momi <command> [options]
 //! ```
 //!
+//! ### Commands
+//!
+//! - `new <filename>`: Create a new document. `momi <filename>` does the same
+//! - `init [dir]`: Create a project whose minutes are written to its `minutes` directory. See [`config::project`]
+//! - `list [dir]`: List the documents in a directory and its subdirectories with their date, author and title
+//! - `show <file>`: Show the metadata of a document
+//! - `edit <file>`: Open a document with the default program
+//! - `export --format <format> <file>...`: Export documents to a standalone HTML page, a Word document, a PDF document,
+//!   or to JSON with a versioned schema. See [`export`]. PDF export is the default `pdf` feature
+//! - `config create`: Create a default configuration file
+//! - `config get <key>`: Print the value of a setting, or tell on stderr that it is not set
+//! - `config set <key> <value>`: Set the value of a setting
+//! - `config unset <key>`: Remove a setting so its default value is used
+//! - `config list`: Print the settings that are set
+//! - `config path [--all]`: Print the path of the configuration file, or of every layer
+//! - `config validate`: Check the configuration files for problems
+//! - `config migrate`: Upgrade the configuration file to the current version, keeping a backup of the original
+//! - `config convert --to <format>`: Convert the configuration file to `toml`, `yaml` or `json`
+//! - `config profiles`: List the names of the profiles
+//!
 //! ### Options
 //!
 //! - `-h, --help`: Show help message and exit
 //! - `-V, --version`: Show version and exit
 //! - `-a, --author <author>`: Add the author of the document
 //! - `-v, --verbose`: Show verbose output
+//! - `--config <path>`: Use the config file at the path on top of the other config files
+//! - `--profile <name>`: Use the named profile of the config. See [`config::profile`]
 //! - `-o, --overwrite`: Overwrite the file if it already exists
+//! - `-e, --enrich`: Ask for the values of the extra metadata labels
+//! - `-f, --field <key=value>`: Add the value of an extra metadata. Can be repeated
+//! - `-t, --template <template>`: Use the named template from the `templates` directory
+//! - `--front-matter <none|yaml|toml>`: Write the metadata as front matter
+//! - `--remember`: Save the author, extension and front matter given here as the defaults, but nothing from a template
+//! - `--output-dir <dir>`: Write the document to the directory, which can have placeholders such as `{{year}}`
+//! - `--stdout`: Print the document to stdout instead of writing it to a file
+//! - `--dry-run`: Print the path and the content of the document without writing anything
+//! - `--format <format>`: Write the document in an export format, such as `docx`, instead of the format of its extension
 //!
 //! #### Example
 //!
@@ -47,15 +80,41 @@
 //!
 //! ```
 //!
+//! ### Exit Codes
+//!
+//! momi exits with 0 on success, 2 for invalid command line arguments,
+//! and the exit code of the [`Error`] for the other errors. See [`error`] for the table of exit codes.
+//!
 //! ## Configuration
 //!
-//! The configuration file is located at `config.json` in the same directory as the executable.
-//! It can hold the following settings:
+//! The configuration is read from the system config, `config.json` next to the executable,
+//! the user config at `$XDG_CONFIG_HOME/momi/config.json`, the project config `.momi/config.json` or `.momi.json`
+//! and the file given with `--config` or `MOMI_CONFIG`, in this order of precedence from the lowest.
+//! They are merged field by field, as described in [`config::layers`].
+//! Every file can be written in TOML, YAML or JSON by its extension, such as `config.toml`,
+//! and `momi config convert --to toml` converts an existing file.
+//! Config files of an older `version` are upgraded in memory, and `momi config migrate` rewrites them,
+//! as described in [`config::migration`].
+//! Each file can hold the following settings:
 //!
 //! - `author`: The default author of the document
 //! - `extension`: The default extension of the document
 //! - `header`: The default header of the document
 //! - `footer`: The default footer of the document
+//! - `rich`: The custom metadata that can be added to the document
+//! - `sections`: The sections written between the header and the footer of the document
+//! - `default_template`: The name of the template used when `-t` is not provided
+//! - `front_matter`: Write the metadata as `yaml` or `toml` front matter instead of `key: value` lines
+//! - `output_dir`: The directory the documents are written to, such as `minutes/{{year}}/{{month}}`.
+//!   It is relative to the project inside a project, and to the current directory outside one
+//! - `filename_pattern`: The filename of the documents without the extension, such as `{{date:%Y-%m-%d}}_{{slug title}}`.
+//!   The title given on the command line is still the title of the document. See [`metadata::placeholder`]
+//! - `profiles`: The named profiles that override the other settings when selected
+//!
+//! The settings can be changed with `momi config set <key> <value>` too.
+//! Structured settings such as `sections` and `rich` are given as JSON.
+//! The config file is only changed by `momi config` and `--remember`;
+//! the values used by the last run are kept in `state.json` in the user config directory instead.
 //!
 //! #### Example
 //!
@@ -102,11 +161,10 @@
 //!
 //! ## Supported Extensions
 //!
-//! These are the extensions that the program can automatically add
-//! to the filename as the title of the document:
-//!
-//! - `.txt`: Text file
-//! - `.md`: Markdown file
+//! The document is written in the format given by its extension: `.md` Markdown, `.org` Org-mode,
+//! `.adoc` AsciiDoc, `.rst` reStructuredText, and plain text for `.txt` and any other extension.
+//! See [`metadata::renderer`] for how each format writes the metadata and the sections.
+//! Only Markdown and plain text can have front matter, and every format is read back by `momi list`, `momi show` and `momi export`.
 //!
 //! ## How to determine which metadata is written to the document?
 //!
@@ -125,6 +183,12 @@
 //!
 //! The rest of the metadata will be written to the document by same rules as the author.
 //!
+//! ## Using momi as a Library
+//!
+//! The minutes can be created without the command line with [`MomBuilder`],
+//! and written to any [`std::io::Write`] with [`Metadata::write_to_doc`] or rendered with [`Metadata::to_doc_string`].
+//! See [`metadata::builder`] for an example.
+//!
 //! ## How to Build
 //!
 //! ### Prerequisites
@@ -160,9 +224,14 @@
 //!
 
 
-mod cli;
 pub mod config;
-mod log_initializer;
+pub mod error;
+pub mod export;
+pub mod log_initializer;
 pub mod metadata;
-mod mom_opener;
+pub mod mom_opener;
+
+pub use error::{Error, Result};
+pub use metadata::builder::MomBuilder;
+pub use metadata::metadata::Metadata;
 
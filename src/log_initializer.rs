@@ -1,23 +1,23 @@
-use crate::cli::Cli;
 use log::LevelFilter;
 use simplelog::CombinedLogger;
 use std::fs;
 use std::path::Path;
 
-pub fn init<P: AsRef<Path>>(current_exe_dir: P, cli: &Cli) {
+/// Log to the terminal and to `mmomlog.log` in the given directory.
+/// The terminal shows only the errors, or every message from the info level on if `verbose` is set.
+///
+/// If the log file cannot be opened, such as in a read-only directory, momi only logs to the terminal,
+/// and if the local time offset is unknown, the log is written in UTC.
+pub fn init<P: AsRef<Path>>(current_exe_dir: P, verbose: bool) {
     let log_file_path = current_exe_dir.as_ref().join("mmomlog.log");
-    let log_file = match Path::is_file(&log_file_path) {
-        true => fs::OpenOptions::new()
-            .read(true)
-            .append(true)
-            .open(&log_file_path)
-            .unwrap(),
-        false => fs::File::create(&log_file_path).unwrap(),
-    };
+    let log_file = fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&log_file_path);
 
     let log_config = simplelog::ConfigBuilder::new()
         .set_time_offset_to_local()
-        .unwrap()
+        .unwrap_or_else(|builder| builder)
         .set_level_color(simplelog::Level::Error, Some(simplelog::Color::Red))
         .set_level_color(simplelog::Level::Warn, Some(simplelog::Color::Yellow))
         .set_level_color(simplelog::Level::Info, Some(simplelog::Color::Green))
@@ -25,7 +25,7 @@ pub fn init<P: AsRef<Path>>(current_exe_dir: P, cli: &Cli) {
         .set_level_color(simplelog::Level::Trace, Some(simplelog::Color::Magenta))
         .build();
 
-    let term_logger = match &cli.verbose {
+    let term_logger = match verbose {
         true => simplelog::TermLogger::new(
             LevelFilter::Info,
             log_config.clone(),
@@ -39,8 +39,9 @@ pub fn init<P: AsRef<Path>>(current_exe_dir: P, cli: &Cli) {
             simplelog::ColorChoice::Never,
         ),
     };
-    let _ = CombinedLogger::init(vec![
-        term_logger,
-        simplelog::WriteLogger::new(LevelFilter::Info, log_config.clone(), log_file),
-    ]);
+    let mut loggers: Vec<Box<dyn simplelog::SharedLogger>> = vec![term_logger];
+    if let Ok(log_file) = log_file {
+        loggers.push(simplelog::WriteLogger::new(LevelFilter::Info, log_config.clone(), log_file));
+    }
+    let _ = CombinedLogger::init(loggers);
 }
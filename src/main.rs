@@ -1,85 +1,196 @@
-
 mod cli;
-pub mod config;
-mod log_initializer;
-pub mod metadata;
-mod mom_opener;
-
 
 use chrono::Local;
 use clap::Parser;
 use log::{error, info, warn};
 use std::{env, io};
 use std::fs;
-use std::io::Error;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
 
-use cli::Cli;
-use config::Config;
-use crate::metadata::metadata::Metadata;
-use crate::mom_opener::Moms;
+use cli::{Cli, Command, ConfigCommand, NewArgs};
+use momi::{Error, Result};
+use momi::config::{keys, layers, migration, Config};
+use momi::config::format::ConfigFormat;
+use momi::config::layers::ConfigLayers;
+use momi::config::profile::PROFILE_ENV;
+use momi::config::project::Project;
+use momi::config::template::Template;
+use momi::export::ExportFormat;
+use momi::log_initializer;
+use momi::metadata::metadata::Metadata;
+use momi::metadata::{parser, prompt, renderer};
+use momi::metadata::renderer::Renderer;
+use momi::metadata::state::{State, STATE_FILE};
+use momi::mom_opener::Moms;
 
 
-fn make_metadata(cli: &Cli) -> Metadata {
-    let current_exe_dir = env::current_exe()
-        .expect("Error getting current executable directory")
-        .parent()
-        .expect("Error getting parent directory")
-        .to_path_buf();
-    let config_file_path = &current_exe_dir.join("config.json");
-    let config = Config::from_file(&config_file_path).unwrap_or_else(|_| {
-        warn!("Error loading config file");
-        let config = Config::new();
-        config.create_config_file(&config_file_path).unwrap();
-        Config::from_file(&config_file_path).unwrap()
-    });
+fn make_metadata(args: &NewArgs, layers: &ConfigLayers, profile: Option<&str>) -> Result<Metadata> {
+    args.validate_filename()?;
+    let mut options = args.to_options();
+    // An export format replaces the format of the extension, so the front matter is checked against it.
+    if let Some(format) = args.format {
+        options.extension = Some(format.extension().to_string());
+    }
+    let mut config = load_layers(layers, profile)?;
     info!("Config loaded successfully");
-    let metadata = Metadata::from(&cli, &config);
-    metadata
+    if let Some(name) = Metadata::determine_template(&options, &config) {
+        let template = Template::from_dirs(&layers.templates_dirs(), &name)?;
+        template.apply_to(&mut config);
+        info!("Template {} applied", name);
+    }
+    Metadata::from(&options, &config)
 }
 
-fn main() -> Result<(), Error> {
-    let cli = Cli::parse();
-
-    let current_exe_dir = env::current_exe()
-        .expect("Error getting current executable directory")
-        .parent()
-        .expect("Error getting parent directory")
-        .to_path_buf();
-    let config_file_path = &current_exe_dir.join("config.json");
+/// Create a default configuration file. `momi config create`
+fn create_config(config_file_path: &Path) -> Result<()> {
+    info!("Trying to create config file");
+    if fs::metadata(config_file_path).is_ok() {
+        return Err(Error::FileExists(config_file_path.to_path_buf()));
+    }
+    let config = Config::default();
+    config.create_config_file(config_file_path)?;
+    info!("Config file created successfully");
+    Ok(())
+}
 
-    log_initializer::init(&current_exe_dir, &cli);
+/// Load the config file for `momi config`. A missing config file is an empty config.
+fn load_config(config_file_path: &Path) -> Result<Config> {
+    if !config_file_path.exists() {
+        return Ok(Config::default());
+    }
+    Config::from_file(config_file_path)
+}
 
-    info!(
-        "--------Start logging at {}--------",
-        Local::now().format("%Y-%m-%d %H:%M:%S").to_string()
-    );
+/// Load and merge the config files of every layer, and apply the given profile.
+fn load_layers(layers: &ConfigLayers, profile: Option<&str>) -> Result<Config> {
+    let mut config = layers.load().map_err(|e| match e {
+        Error::ConfigParse(message) => {
+            Error::ConfigParse(format!("{}. Run `momi config validate` for details", message))
+        }
+        e => e,
+    })?;
+    if let Some(profile) = profile {
+        config.apply_profile(profile)?;
+        info!("Profile {} applied", profile);
+    }
+    Ok(config)
+}
 
-    match cli.create_config {
-        true => {
-            info!("Trying to create config file");
-            match fs::metadata(&config_file_path) {
-                Ok(_) => {
-                    error!("Config file already exists");
-                    panic!("Config file already exists");
+/// Run a `momi config` subcommand.
+///
+/// `get`, `list` and `profiles` show the merged settings of every layer with the given profile applied,
+/// while the other subcommands work on the config file given by [`ConfigLayers::target`].
+fn config(command: &ConfigCommand, layers: &ConfigLayers, config_file_path: &Path, profile: Option<&str>) -> Result<()> {
+    match command {
+        ConfigCommand::Create => create_config(config_file_path)?,
+        ConfigCommand::Get { key } => {
+            match load_layers(layers, profile)?.get(key)? {
+                Some(value) => println!("{}", value),
+                // An unset setting prints nothing on stdout, so `$(momi config get key)` is empty.
+                None => eprintln!("{} is not set", key),
+            }
+        }
+        ConfigCommand::Set { key, value } => {
+            let mut config = load_config(config_file_path)?;
+            config.set(key, value)?;
+            config.create_config_file(config_file_path)?;
+            info!("{} set to {}", key, value);
+        }
+        ConfigCommand::Unset { key } => {
+            let mut config = load_config(config_file_path)?;
+            config.unset(key)?;
+            config.create_config_file(config_file_path)?;
+            info!("{} unset", key);
+        }
+        ConfigCommand::List => {
+            for (key, value) in load_layers(layers, profile)?.list()? {
+                println!("{} = {}", key, value);
+            }
+        }
+        ConfigCommand::Profiles => {
+            for name in load_layers(layers, None)?.profile_names() {
+                println!("{}", name);
+            }
+        }
+        ConfigCommand::Migrate => match migration::migrate_file(config_file_path)? {
+            Some(backup) => println!(
+                "Migrated {} to version {}. The original is kept as {}",
+                config_file_path.display(),
+                migration::CONFIG_VERSION,
+                backup.display()
+            ),
+            None => println!("{} is up to date", config_file_path.display()),
+        },
+        ConfigCommand::Convert { to } => {
+            let converted = Config::convert_file(config_file_path, *to)?;
+            println!("Converted {} to {}", config_file_path.display(), converted.display());
+        }
+        ConfigCommand::Path { all: false } => println!("{}", config_file_path.display()),
+        ConfigCommand::Path { all: true } => {
+            for (layer, path) in &layers.layers {
+                match path.is_file() {
+                    true => println!("{:<10}  {}", layer, path.display()),
+                    false => println!("{:<10}  {} (not found)", layer, path.display()),
                 }
-                Err(_) => (),
             }
-            let config = Config::default();
-            config.create_config_file(&config_file_path).unwrap();
-            info!("Config file created successfully");
-            return Ok(());
         }
-        false => (),
+        ConfigCommand::Validate => {
+            let templates = Template::list_all(&layers.templates_dirs());
+            let mut problem_count = 0;
+            for (_, path) in layers.layers.iter().filter(|(_, path)| path.is_file()) {
+                let config_json = fs::read_to_string(path)
+                    .map_err(|source| Error::ConfigIo { path: path.clone(), source })?;
+                let problems = keys::validate(&config_json, ConfigFormat::from_path(path), &templates);
+                match problems.is_empty() {
+                    true => println!("{} is valid", path.display()),
+                    false => {
+                        for problem in &problems {
+                            println!("{}: {}", path.display(), problem);
+                        }
+                    }
+                }
+                problem_count += problems.len();
+            }
+            if problem_count > 0 {
+                return Err(Error::ConfigParse(format!("The config files have {} problem(s)", problem_count)));
+            }
+        }
     }
+    Ok(())
+}
 
-    let metadata = make_metadata(&cli);
+/// The path the document is written to.
+/// - `--output-dir` is relative to the current directory, like the other paths on the command line.
+/// - `output_dir` of the config is relative to the project, so it means the same anywhere inside it,
+///   or to the current directory outside a project.
+/// - Without an output directory, the minutes of a project are written to its minutes directory,
+///   and the others to the current directory.
+fn output_path(args: &NewArgs, metadata: &Metadata) -> Result<PathBuf> {
+    let current_dir = env::current_dir()?;
+    let output_dir = match (&metadata.output_dir, Project::discover(&current_dir)) {
+        (Some(output_dir), _) if args.output_dir.is_some() => current_dir.join(output_dir),
+        (Some(output_dir), Some(project)) => project.root.join(output_dir),
+        (Some(output_dir), None) => current_dir.join(output_dir),
+        (None, Some(project)) => {
+            info!("Writing to the minutes directory of the project {}", project.root.display());
+            project.minutes_dir()
+        }
+        (None, None) => current_dir,
+    };
+    Ok(output_dir.join(metadata.file_name()))
+}
 
-    let new_file_path = env::current_dir()?.join(match &metadata.extension {
-        Some(extension) => format!("{}.{}", &metadata.filestem, extension),
-        None => metadata.filestem.clone(),
-    });
+/// Write the documents to the given path with the given renderer, one after the other,
+/// creating its directory if needed.
+fn write_docs(metadatas: &[Metadata], renderer: &dyn Renderer, new_file_path: &Path, overwrite: bool) -> Result<()> {
+    let write_error = |source| Error::Write { path: new_file_path.to_path_buf(), source };
+    if let Some(output_dir) = new_file_path.parent() {
+        fs::create_dir_all(output_dir).map_err(write_error)?;
+    }
     let mut new_file_options = fs::OpenOptions::new();
-    match &cli.overwrite {
+    match overwrite {
         true => {
             info!("Overwriting flag set true. Overwriting file if exists");
             new_file_options.write(true).create(true).truncate(true);
@@ -89,41 +200,280 @@ fn main() -> Result<(), Error> {
             new_file_options.write(true).create_new(true);
         }
     }
-    let mut new_file = match new_file_options.open(&new_file_path) {
-        Ok(file) => file,
-        Err(e) => match e.kind() {
-            io::ErrorKind::AlreadyExists => {
-                error!("File already exists. Use -o to overwrite");
-                panic!("File already exists. Use -o to overwrite");
-            },
-            e => {
-                error!("Error opening file: {}", e);
-                panic!("Error opening file: {}", e);
-            },
-        }
+    let mut new_file = new_file_options.open(new_file_path).map_err(|source| match source.kind() {
+        io::ErrorKind::AlreadyExists => Error::FileExists(new_file_path.to_path_buf()),
+        _ => write_error(source),
+    })?;
+    for metadata in metadatas {
+        renderer.render(metadata, &mut new_file).map_err(|e| match e {
+            Error::Io(source) => write_error(source),
+            e => e,
+        })?;
+    }
+    Ok(())
+}
+
+/// Create a new document. `momi new <filename>` or `momi <filename>`
+///
+/// With `--stdout` the document is printed instead of written, and the `--enrich` prompts go to stderr.
+/// With `--dry-run` the path and the document are printed and nothing is written,
+/// not even the state file or the defaults of `--remember`.
+/// With `--format` the document is written in an export format, such as docx.
+fn new(
+    args: &NewArgs,
+    layers: &ConfigLayers,
+    config_file_path: &Path,
+    state_dir: &Path,
+    profile: Option<&str>,
+) -> Result<()> {
+    let mut metadata = make_metadata(args, layers, profile)?;
+    let renderer = match args.format {
+        Some(format) => format.renderer(),
+        None => renderer::for_extension(metadata.extension.as_deref()),
     };
+    if args.enrich {
+        // The document printed with --stdout must not be mixed with the prompts.
+        let mut prompt_output: Box<dyn Write> = match args.stdout {
+            true => Box::new(io::stderr()),
+            false => Box::new(io::stdout()),
+        };
+        metadata.extra_values = prompt::ask_extra_values(
+            metadata.extra_values,
+            metadata.extra_metadata.as_deref().unwrap_or_default(),
+            &mut io::stdin().lock(),
+            &mut prompt_output,
+        )?;
+    }
+    metadata.validate_extra_values()?;
+    metadata.render_placeholders()?;
 
-    metadata.write_to_doc(&mut new_file, &cli).unwrap_or_else(|_| {
-        error!("Error writing metadata to file");
-        panic!("Error writing metadata to file");
+    if args.stdout {
+        renderer.render(&metadata, &mut io::stdout().lock())?;
+    } else {
+        let new_file_path = output_path(args, &metadata)?;
+        if !args.overwrite && new_file_path.exists() {
+            info!("Use -o to overwrite the file");
+            return Err(Error::FileExists(new_file_path));
+        }
+        if args.dry_run {
+            println!("Would write {}", new_file_path.display());
+            // An export format such as docx is not printable, so only its path is printed.
+            if args.format.is_none() {
+                print!("{}", metadata.to_doc_string()?);
+            }
+            // Nothing is written on a dry run, so the state and the defaults are left as they are.
+            return Ok(());
+        }
+        write_docs(std::slice::from_ref(&metadata), renderer.as_ref(), &new_file_path, args.overwrite)?;
+        if args.open {
+            info!("Trying to open file with default program");
+            Moms { moms: vec![new_file_path.to_string_lossy().to_string()] }.open_all()?;
+        }
+    }
+
+    // The values of this run are kept in the state file, not in the config file.
+    State::from(&metadata).to_file(state_dir.join(STATE_FILE)).unwrap_or_else(|e| {
+        warn!("Error writing state file: {}", e);
     });
+    if args.remember {
+        let mut config = load_config(config_file_path)?;
+        Metadata::remember(&args.to_options(), &mut config);
+        config.create_config_file(config_file_path)?;
+        info!("Defaults saved to config file");
+    }
+    Ok(())
+}
+
+/// Create a project in the given directory. `momi init [dir]`
+fn init(dir: Option<PathBuf>) -> Result<()> {
+    let dir = match dir {
+        Some(dir) => dir,
+        None => env::current_dir()?,
+    };
+    let project = Project::init(&dir)?;
+    println!("Created {}", project.dir().display());
+    Ok(())
+}
+
+/// List the documents in the given directory and its subdirectories, oldest first. `momi list [dir]`
+///
+/// Without a directory, the minutes directory of the current project or the current directory is listed.
+/// Files that are not documents written by momi are skipped.
+fn list(dir: Option<PathBuf>) -> Result<()> {
+    let dir = match dir {
+        Some(dir) => dir,
+        None => match Project::discover(env::current_dir()?) {
+            Some(project) => project.minutes_dir(),
+            None => env::current_dir()?,
+        },
+    };
+    let mut documents = vec![];
+    for mom in Moms::from_dir(&dir)?.moms {
+        match parser::parse_file(&mom) {
+            Ok(metadata) => documents.push((metadata, mom)),
+            Err(e) => info!("Skipping {}: {}", mom, e),
+        }
+    }
+    documents.sort_by_key(|(metadata, _)| metadata.datetime);
+    for (metadata, mom) in documents {
+        // The documents in subdirectories, such as minutes/2024/05, are shown with their subdirectory.
+        let filename = Path::new(&mom)
+            .strip_prefix(&dir)
+            .ok()
+            .and_then(|filename| filename.to_str())
+            .unwrap_or(&mom);
+        println!(
+            "{}  {}  {}  ({})",
+            metadata.datetime.format("%Y-%m-%d %H:%M:%S"),
+            metadata.author,
+            metadata.filestem,
+            filename
+        );
+    }
+    Ok(())
+}
 
-    info!("Trying to open file with default program");
-    let moms = Moms { moms: vec![new_file_path.to_str().unwrap().to_string()] };
+/// Read the given document, naming it in the error.
+fn read_doc(file: &Path) -> Result<Metadata> {
+    parser::parse_file(file).map_err(|e| match e {
+        Error::DocumentParse(message) => Error::DocumentParse(format!("{}: {}", file.display(), message)),
+        Error::Io(e) => Error::Io(io::Error::new(e.kind(), format!("{}: {}", file.display(), e))),
+        e => e,
+    })
+}
+
+/// Show the metadata of the given document. `momi show <file>`
+fn show(file: &Path) -> Result<()> {
+    print!("{}", read_doc(file)?);
+    Ok(())
+}
 
-    match cli.open {
-        true => moms.open_all(),
-        false => (),
+/// Export the given documents to the given format. `momi export --format <format> <file>...`
+///
+/// Every exported file is written next to its document with the extension of the format, unless `--output` is given.
+/// With ndjson the documents are written as the lines of a single file, `--output` or stdout.
+fn export(files: &[PathBuf], format: ExportFormat, output: Option<&Path>, overwrite: bool, stdout: bool) -> Result<()> {
+    let renderer = format.renderer();
+    if format == ExportFormat::Ndjson {
+        let metadatas = files.iter().map(|file| read_doc(file)).collect::<Result<Vec<_>>>()?;
+        return match output {
+            Some(output) => {
+                if !overwrite && output.exists() {
+                    info!("Use -o to overwrite the file");
+                    return Err(Error::FileExists(output.to_path_buf()));
+                }
+                write_docs(&metadatas, renderer.as_ref(), output, overwrite)?;
+                println!("Exported {}", output.display());
+                Ok(())
+            }
+            None => metadatas.iter().try_for_each(|metadata| renderer.render(metadata, &mut io::stdout().lock())),
+        };
+    }
+    if files.len() > 1 && (output.is_some() || stdout) {
+        return Err(Error::InvalidSetting(
+            "--output and --stdout take a single document, except with --format ndjson".to_string(),
+        ));
     }
 
-    metadata.to_config_file(&config_file_path).unwrap_or_else(|_| {
-        error!("Error writing metadata to config file");
-        panic!("Error writing metadata to config file");
-    });
+    for file in files {
+        let metadata = read_doc(file)?;
+        if stdout {
+            renderer.render(&metadata, &mut io::stdout().lock())?;
+            continue;
+        }
+        let export_path = match output {
+            Some(output) => output.to_path_buf(),
+            None => file.with_extension(format.extension()),
+        };
+        if !overwrite && export_path.exists() {
+            info!("Use -o to overwrite the file");
+            return Err(Error::FileExists(export_path));
+        }
+        write_docs(std::slice::from_ref(&metadata), renderer.as_ref(), &export_path, overwrite)?;
+        println!("Exported {}", export_path.display());
+    }
+    Ok(())
+}
+
+/// Open the given document with the default program. `momi edit <file>`
+fn edit(file: &Path) -> Result<()> {
+    if !file.is_file() {
+        return Err(Error::Open { path: file.to_path_buf(), message: "The file does not exist".to_string() });
+    }
+    Moms { moms: vec![file.to_string_lossy().to_string()] }.open_all()
+}
+
+fn run(cli: &Cli) -> Result<()> {
+    let current_exe_dir = env::current_exe()?
+        .parent()
+        .map(Path::to_path_buf)
+        .unwrap_or_default();
+    // The log and the state file are kept in the user config directory,
+    // so momi works when the executable is installed in a read-only directory.
+    let state_dir = match layers::user_config_dir() {
+        Some(dir) if fs::create_dir_all(&dir).is_ok() => dir,
+        _ => current_exe_dir.clone(),
+    };
+
+    log_initializer::init(&state_dir, cli.verbose);
+
+    info!(
+        "--------Start logging at {}--------",
+        Local::now().format("%Y-%m-%d %H:%M:%S")
+    );
+
+    let layers = ConfigLayers::discover(cli.config.clone(), &current_exe_dir, env::current_dir()?);
+    let config_file_path = layers
+        .target()
+        .map(Path::to_path_buf)
+        .unwrap_or_else(|| current_exe_dir.join(layers::CONFIG_FILE));
+    info!("Config files: {:?}", layers.layers);
+    let profile = cli
+        .profile
+        .clone()
+        .or_else(|| env::var(PROFILE_ENV).ok().filter(|profile| !profile.is_empty()));
+
+    if cli.create_config {
+        eprintln!("warning: --create-config is deprecated, use `momi config create` instead");
+        create_config(&config_file_path)?;
+        return Ok(());
+    }
+
+    match &cli.command {
+        Some(Command::New(args)) => new(args, &layers, &config_file_path, &state_dir, profile.as_deref())?,
+        None => match &cli.new {
+            Some(args) => new(args, &layers, &config_file_path, &state_dir, profile.as_deref())?,
+            None => unreachable!("momi without arguments prints the help"),
+        },
+        Some(Command::Init { dir }) => init(dir.clone())?,
+        Some(Command::List { dir }) => list(dir.clone())?,
+        Some(Command::Show { file }) => show(file)?,
+        Some(Command::Edit { file }) => edit(file)?,
+        Some(Command::Export { files, format, output, overwrite, stdout }) => {
+            export(files, *format, output.as_deref(), *overwrite, *stdout)?
+        }
+        Some(Command::Config(command)) => config(command, &layers, &config_file_path, profile.as_deref())?,
+    }
 
     info!(
         "--------End logging at {}--------",
-        Local::now().format("%Y-%m-%d %H:%M:%S").to_string()
+        Local::now().format("%Y-%m-%d %H:%M:%S")
     );
     Ok(())
 }
+
+/// Run momi and exit with the exit code of the error, if any. See [`momi::error`] for the exit codes.
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    match run(&cli) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            match log::max_level() {
+                // The log is not initialized if momi failed before initializing it.
+                log::LevelFilter::Off => eprintln!("{}", e),
+                _ => error!("{}", e),
+            }
+            ExitCode::from(e.exit_code())
+        }
+    }
+}
@@ -0,0 +1,194 @@
+//! Builder to create the metadata of a document without the command line.
+//!
+//! ```
+//! use momi::MomBuilder;
+//!
+//! let metadata = MomBuilder::new("Weekly sync")
+//!     .author("John Doe")
+//!     .extension("md")
+//!     .field("location", "Seoul")
+//!     .build()
+//!     .unwrap();
+//! let document = metadata.to_doc_string().unwrap();
+//! assert!(document.starts_with("# Weekly sync\n"));
+//! ```
+//!
+//! The values given to the builder take precedence over the [`Config`], the same as the command line options.
+//!
+
+use std::path::PathBuf;
+use chrono::{DateTime, Local};
+use crate::config::Config;
+use crate::config::front_matter::FrontMatter;
+use crate::config::template::Template;
+use crate::error::Result;
+use crate::metadata::metadata::Metadata;
+use crate::metadata::options::NewOptions;
+
+/// Builder of the [`Metadata`] of a new document.
+#[derive(Debug, Default)]
+pub struct MomBuilder {
+    options: NewOptions,
+    config: Config,
+    templates_dirs: Vec<PathBuf>,
+    datetime: Option<DateTime<Local>>,
+}
+
+impl MomBuilder {
+    /// Start building the metadata of a document with the given title.
+    pub fn new<S: Into<String>>(title: S) -> Self {
+        MomBuilder {
+            options: NewOptions { title: title.into(), ..Default::default() },
+            ..Default::default()
+        }
+    }
+
+    /// The config used for the values that are not given to the builder. The default is an empty config.
+    pub fn config(mut self, config: Config) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// The author of the document.
+    pub fn author<S: Into<String>>(mut self, author: S) -> Self {
+        self.options.author = Some(author.into());
+        self
+    }
+
+    /// The extension of the document, without the dot.
+    pub fn extension<S: Into<String>>(mut self, extension: S) -> Self {
+        self.options.extension = Some(extension.into());
+        self
+    }
+
+    /// The value of an extra metadata. Can be called several times.
+    pub fn field<K: Into<String>, V: Into<String>>(mut self, key: K, value: V) -> Self {
+        self.options.fields.push((key.into(), value.into()));
+        self
+    }
+
+    /// Write every extra metadata of the config, not only the fields given to the builder.
+    pub fn enrich(mut self, enrich: bool) -> Self {
+        self.options.enrich = enrich;
+        self
+    }
+
+    /// The name of the template to use, looked up in the directories given by [`MomBuilder::templates_dir`].
+    pub fn template<S: Into<String>>(mut self, template: S) -> Self {
+        self.options.template = Some(template.into());
+        self
+    }
+
+    /// A directory to look up the templates in. Can be called several times,
+    /// and the directories given first take precedence.
+    pub fn templates_dir<P: Into<PathBuf>>(mut self, templates_dir: P) -> Self {
+        self.templates_dirs.push(templates_dir.into());
+        self
+    }
+
+    /// The format of the front matter.
+    pub fn front_matter(mut self, front_matter: FrontMatter) -> Self {
+        self.options.front_matter = Some(front_matter);
+        self
+    }
+
+    /// The directory the document is written to, which can have placeholders such as `minutes/{{year}}`.
+    /// The builder only renders it to [`Metadata::output_dir`]; nothing is written.
+    pub fn output_dir<S: Into<String>>(mut self, output_dir: S) -> Self {
+        self.options.output_dir = Some(output_dir.into());
+        self
+    }
+
+    /// The date and time the document is created. The default is now.
+    pub fn datetime(mut self, datetime: DateTime<Local>) -> Self {
+        self.datetime = Some(datetime);
+        self
+    }
+
+    /// Build the metadata with the template applied, the extra metadata validated and the placeholders rendered.
+    ///
+    /// The title is not a filename, so a dot in it does not start an extension.
+    pub fn build(self) -> Result<Metadata> {
+        self.options.validate_title()?;
+        let mut config = self.config;
+        if let Some(name) = Metadata::determine_template(&self.options, &config) {
+            Template::from_dirs(&self.templates_dirs, &name)?.apply_to(&mut config);
+        }
+        let mut metadata = Metadata::from(&self.options, &config)?;
+        if let Some(datetime) = self.datetime {
+            metadata.datetime = datetime;
+        }
+        metadata.validate_extra_values()?;
+        metadata.render_placeholders()?;
+        Ok(metadata)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::Error;
+    use std::fs;
+    use chrono::TimeZone;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_build() {
+        let mut config = Config::new();
+        config.set_author("Jane Doe".to_string());
+        config.set_extension(Some("txt".to_string()));
+        config.set_header(Some("{{title}} by {{author}}".to_string()));
+        let metadata = MomBuilder::new("Release v1.2")
+            .config(config)
+            .author("John Doe")
+            .field("location", "Seoul")
+            .datetime(Local.with_ymd_and_hms(2024, 4, 30, 4, 58, 44).unwrap())
+            .build()
+            .unwrap();
+        assert_eq!(metadata.filestem, "Release v1.2");
+        assert_eq!(metadata.extension.as_deref(), Some("txt"));
+        assert_eq!(metadata.header.as_deref(), Some("Release v1.2 by John Doe"));
+        assert_eq!(
+            metadata.to_doc_string().unwrap(),
+            "Release v1.2\n\ncreated: 2024-04-30 04:58:44\nauthor: John Doe\n\nlocation: Seoul\n\nRelease v1.2 by John Doe\n\n"
+        );
+    }
+
+    #[test]
+    fn test_build_with_template() {
+        let dir = tempdir().unwrap();
+        fs::write(dir.path().join("retro.json"), r#"{"header": "Retrospective"}"#).unwrap();
+        let metadata = MomBuilder::new("Sprint 12")
+            .template("retro")
+            .templates_dir(dir.path())
+            .build()
+            .unwrap();
+        assert_eq!(metadata.header.as_deref(), Some("Retrospective"));
+        assert_eq!(metadata.template.as_deref(), Some("retro"));
+        assert!(matches!(
+            MomBuilder::new("Sprint 12").template("standup").build(),
+            Err(Error::UnknownTemplate(_))
+        ));
+    }
+
+    #[test]
+    fn test_build_with_empty_title() {
+        assert!(matches!(MomBuilder::new(" ").build(), Err(Error::InvalidFilename(_))));
+        assert!(matches!(MomBuilder::new("..").build(), Err(Error::InvalidFilename(_))));
+    }
+
+    #[test]
+    fn test_build_with_dotted_title() {
+        let metadata = MomBuilder::new("Sync.org").extension("md").front_matter(FrontMatter::Yaml).build().unwrap();
+        assert_eq!(metadata.filestem, "Sync.org");
+        assert_eq!(metadata.extension.as_deref(), Some("md"));
+        assert!(matches!(
+            MomBuilder::new("Sync.md").extension("org").front_matter(FrontMatter::Yaml).build(),
+            Err(Error::InvalidSetting(_))
+        ));
+        let mut config = Config::new();
+        config.set_extension(Some("rst".to_string()));
+        let built = MomBuilder::new("Sync.md").config(config).front_matter(FrontMatter::Toml).build();
+        assert!(matches!(built, Err(Error::InvalidSetting(_))));
+    }
+}
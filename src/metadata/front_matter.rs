@@ -0,0 +1,169 @@
+//! YAML and TOML front matter of the document.
+//!
+//! The front matter holds the title, the creation date, the author and the extra metadata of the document,
+//! so static site generators and note tools can read them.
+//! The values of typed extra metadata are written with their types,
+//! such as a list for `people` fields and a boolean for `boolean` fields.
+//!
+
+use serde::ser::{Serialize, SerializeMap, Serializer};
+use serde_derive::Serialize;
+use crate::config::front_matter::FrontMatter;
+use crate::config::rich_metadata::FieldType;
+use crate::error::Error;
+use crate::metadata::metadata::Metadata;
+
+/// A value of the front matter.
+#[derive(Serialize, Debug, PartialEq)]
+#[serde(untagged)]
+enum Value {
+    Text(String),
+    List(Vec<String>),
+    Boolean(bool),
+    Integer(i64),
+    Float(f64),
+}
+
+impl Value {
+    /// Convert the given value of an extra metadata according to the type of its field.
+    fn typed(value: &str, field_type: FieldType) -> Self {
+        match field_type {
+            FieldType::People => Value::List(
+                value
+                    .split(',')
+                    .map(|person| person.trim().to_string())
+                    .filter(|person| !person.is_empty())
+                    .collect(),
+            ),
+            FieldType::Boolean => match value.parse::<bool>() {
+                Ok(boolean) => Value::Boolean(boolean),
+                Err(_) => Value::Text(value.to_string()),
+            },
+            FieldType::Number => match (value.parse::<i64>(), value.parse::<f64>()) {
+                (Ok(integer), _) => Value::Integer(integer),
+                (_, Ok(float)) => Value::Float(float),
+                _ => Value::Text(value.to_string()),
+            },
+            _ => Value::Text(value.to_string()),
+        }
+    }
+}
+
+/// The entries of the front matter in the order they are written.
+struct Entries(Vec<(String, Value)>);
+
+impl Serialize for Entries {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(Some(self.0.len()))?;
+        for (key, value) in &self.0 {
+            map.serialize_entry(key, value)?;
+        }
+        map.end()
+    }
+}
+
+/// Collect the entries of the front matter from the given metadata.
+fn entries(metadata: &Metadata) -> Entries {
+    let mut entries = vec![
+        ("title".to_string(), Value::Text(metadata.filestem.clone())),
+        ("created".to_string(), Value::Text(metadata.datetime.to_rfc3339())),
+        ("author".to_string(), Value::Text(metadata.author.clone())),
+    ];
+    for (label, value) in &metadata.extra_values {
+        let field_type = metadata
+            .find_extra_field(label)
+            .map(|field| field.field_type)
+            .unwrap_or_default();
+        entries.push((label.clone(), Value::typed(value, field_type)));
+    }
+    Entries(entries)
+}
+
+/// Render the front matter block of the given metadata, including the delimiters.
+///
+/// Returns an empty string if the front matter is [`FrontMatter::None`].
+pub fn render(metadata: &Metadata, front_matter: FrontMatter) -> crate::Result<String> {
+    let body = match front_matter {
+        FrontMatter::None => return Ok(String::new()),
+        FrontMatter::Yaml => serde_yaml::to_string(&entries(metadata)).map_err(|e| Error::Render(e.to_string()))?,
+        FrontMatter::Toml => toml::to_string(&entries(metadata)).map_err(|e| Error::Render(e.to_string()))?,
+    };
+    let delimiter = front_matter.delimiter().unwrap_or_default();
+    Ok(format!("{}\n{}{}\n", delimiter, body, delimiter))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Local, TimeZone};
+    use crate::config::rich_metadata::ExtraField;
+
+    fn metadata() -> Metadata {
+        Metadata {
+            filestem: "Weekly sync".to_string(),
+            author: "John Doe".to_string(),
+            datetime: Local.with_ymd_and_hms(2024, 5, 2, 9, 30, 0).unwrap(),
+            extra_metadata: Some(vec![
+                ExtraField {
+                    field_type: FieldType::People,
+                    ..ExtraField::from("attendees")
+                },
+                ExtraField {
+                    field_type: FieldType::Number,
+                    ..ExtraField::from("duration")
+                },
+            ]),
+            extra_values: vec![
+                ("location".to_string(), "Room 301".to_string()),
+                ("attendees".to_string(), "John, Jane".to_string()),
+                ("duration".to_string(), "30".to_string()),
+            ],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_render_none() {
+        assert_eq!(render(&metadata(), FrontMatter::None).unwrap(), "");
+    }
+
+    #[test]
+    fn test_render_yaml() {
+        let metadata = metadata();
+        assert_eq!(
+            render(&metadata, FrontMatter::Yaml).unwrap(),
+            format!(
+                "---\n\
+                title: Weekly sync\n\
+                created: {}\n\
+                author: John Doe\n\
+                location: Room 301\n\
+                attendees:\n\
+                - John\n\
+                - Jane\n\
+                duration: 30\n\
+                ---\n",
+                metadata.datetime.to_rfc3339()
+            )
+        );
+    }
+
+    #[test]
+    fn test_render_toml() {
+        let metadata = metadata();
+        assert_eq!(
+            render(&metadata, FrontMatter::Toml).unwrap(),
+            format!(
+                "+++\n\
+                title = \"Weekly sync\"\n\
+                created = \"{}\"\n\
+                author = \"John Doe\"\n\
+                location = \"Room 301\"\n\
+                attendees = [\"John\", \"Jane\"]\n\
+                duration = 30\n\
+                +++\n",
+                metadata.datetime.to_rfc3339()
+            )
+        );
+    }
+}
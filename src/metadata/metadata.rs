@@ -1,17 +1,20 @@
 //! Metadata module to handle the metadata of the file.
 //!
 
-use std::fs::File;
+use std::fmt;
 use std::io::Write;
-use std::path::{Path, PathBuf};
 use chrono::{DateTime, Local};
 use log::{info, warn};
-use crate::cli::Cli;
 use crate::config::Config;
-use crate::config::rich_metadata::RichMetadata;
+use crate::config::front_matter::FrontMatter;
+use crate::config::rich_metadata::{ExtraField, RESERVED_LABELS};
+use crate::config::section::Section;
+use crate::error::{Error, Result};
+use crate::metadata::{placeholder, renderer};
+use crate::metadata::options::NewOptions;
 
 /// Metadata struct to hold the metadata of the file.
-#[derive(Default, Debug)]
+#[derive(Default, Debug, Clone)]
 pub struct Metadata {
     /// The filestem of the file without the extension
     pub filestem: String,
@@ -26,37 +29,43 @@ pub struct Metadata {
     /// The footer of the file
     pub footer: Option<String>,
     /// The extra metadata of the file
-    pub extra_metadata: Option<Vec<String>>,
+    pub extra_metadata: Option<Vec<ExtraField>>,
+    /// The labels and values of the extra metadata written to the file
+    pub extra_values: Vec<(String, String)>,
+    /// The sections of the file, sorted by their order
+    pub sections: Option<Vec<Section>>,
+    /// The name of the template used for the file
+    pub template: Option<String>,
+    /// The format of the front matter of the file
+    pub front_matter: FrontMatter,
+    /// The directory the file is written to, relative to the current directory or the project
+    pub output_dir: Option<String>,
+    /// The filename of the file without the extension if it is not the title, from the filename pattern
+    pub filename: Option<String>,
 }
 
 impl Metadata {
 
-    /// Determine the filestem from the given filename
-    fn determine_filestem(cli: &Cli) -> String {
-        let filename = &cli.filename;
-        let filestem = PathBuf::from(&filename)
-            .file_stem()
-            .unwrap()
-            .to_str()
-            .unwrap()
-            .to_string();
-        filestem
+    /// Determine the filestem from the given title.
+    /// A title that cannot be a filename, such as `..` or `/`, is an error.
+    fn determine_filestem(options: &NewOptions) -> Result<String> {
+        options.validate_title()?;
+        Ok(options.title.clone())
     }
 
-    /// Determine the extension from the given filename or the config file.
-    /// - If the extension is not provided in the filename, use the extension from the config file.
+    /// Determine the extension from the given extension or the config file.
+    /// - If the extension is not provided, use the extension from the config file.
     /// - If the extension is not provided in the config file, do not use the extension.
-    /// - If the extension is provided in the filename, use the extension from the filename.
-    /// - If the extension is provided in the filename and the config file, use the extension from the filename.
-    fn determine_extension(cli: &Cli, config: &Config) -> Option<String> {
-        match cli.get_extension_if_exists() {
+    /// - If the extension is provided, use it even if the config file has another one.
+    fn determine_extension(options: &NewOptions, config: &Config) -> Option<String> {
+        match options.extension.clone() {
             Some(extension) => {
                 info!("Extension provided: {}", extension);
                 Some(extension)
             }
             None => {
                 info!("Extension not provided. Using extension from config file");
-                match &config.extension {
+                match &config.settings.extension {
                     Some(extension) => Some(extension.clone()),
                     None => {
                         warn!("Extension not found in config file. Not using extension");
@@ -71,15 +80,15 @@ impl Metadata {
     /// - If the author is provided in the author option, use the author from the author option.
     /// - If the author is not provided in the author option, use the author from the config file.
     /// - If the author is not provided in the author option and the config file, use the current user's name.
-    fn determine_author(cli: &Cli, config: &Config) -> String {
-        match &cli.author {
+    fn determine_author(options: &NewOptions, config: &Config) -> String {
+        match &options.author {
             Some(author) => {
                 info!("Author provided: {}", author);
                 author.clone()
             }
             None => {
                 info!("Author not provided. Using author from config file");
-                match &config.author {
+                match &config.settings.author {
                     Some(author) => author.clone(),
                     None => {
                         warn!("Author not found in config file. Using current user to author");
@@ -90,227 +99,757 @@ impl Metadata {
         }
     }
 
-    /// Write the metadata to the given file.
-    pub fn to_config_file<P: AsRef<Path>>(&self, config_file: P) -> Result<(), Box<dyn std::error::Error>> {
-        let mut config = Config::new();
-        config.set_author(self.author.clone());
-        config.set_extension(self.extension.clone());
-        config.set_header(self.header.clone());
-        config.set_footer(self.footer.clone());
-        config.set_rich(RichMetadata {
-            extra_metadata: self.extra_metadata.clone().unwrap_or_default(),
-        });
-        config.create_config_file(config_file)
+    /// Save the values given by the user as the defaults in the given config.
+    ///
+    /// Only the author, the extension and the front matter are saved,
+    /// so the values that come from the config file or a template are not written back to it.
+    /// The template itself is not saved either; `default_template` is only changed with `momi config set`.
+    pub fn remember(options: &NewOptions, config: &mut Config) {
+        if let Some(author) = &options.author {
+            info!("Remembering author: {}", author);
+            config.set_author(author.clone());
+        }
+        if let Some(extension) = options.extension.clone() {
+            info!("Remembering extension: {}", extension);
+            config.set_extension(Some(extension));
+        }
+        if let Some(front_matter) = options.front_matter {
+            info!("Remembering front matter: {:?}", front_matter);
+            config.set_front_matter(Some(front_matter));
+        }
+    }
+
+    /// Determine the template from the given template option or the config file.
+    /// - If the template is provided in the template option, use the template from the template option.
+    /// - If the template is not provided in the template option, use the default template from the config file.
+    /// - If the template is not provided in the template option and the config file, do not use a template.
+    pub fn determine_template(options: &NewOptions, config: &Config) -> Option<String> {
+        match &options.template {
+            Some(template) => {
+                info!("Template provided: {}", template);
+                Some(template.clone())
+            }
+            None => {
+                info!("Template not provided. Using default template from config file");
+                config.settings.default_template.clone()
+            }
+        }
+    }
+
+    /// Determine the front matter from the given front matter option or the config file.
+    /// - If the front matter is provided in the front matter option, use the front matter from the front matter option.
+    /// - If the front matter is not provided in the front matter option, use the front matter from the config file.
+    /// - If the front matter is not provided in the front matter option and the config file, do not use front matter.
+    fn determine_front_matter(options: &NewOptions, config: &Config) -> FrontMatter {
+        match options.front_matter {
+            Some(front_matter) => {
+                info!("Front matter provided: {:?}", front_matter);
+                front_matter
+            }
+            None => {
+                info!("Front matter not provided. Using front matter from config file");
+                config.settings.front_matter.unwrap_or_default()
+            }
+        }
+    }
+
+    /// Determine the directory the document is written to from the given output directory option or the config file.
+    /// - If the output directory is provided in the output directory option, use the output directory from the option.
+    /// - If the output directory is not provided in the option, use the output directory from the config file.
+    /// - If the output directory is not provided in the option and the config file, use the default directory.
+    fn determine_output_dir(options: &NewOptions, config: &Config) -> Option<String> {
+        match &options.output_dir {
+            Some(output_dir) => {
+                info!("Output directory provided: {}", output_dir);
+                Some(output_dir.clone())
+            }
+            None => config.settings.output_dir.clone(),
+        }
     }
 
-    /// Create a new Metadata struct from the given Cli and Config.
-    pub fn from(cli: &Cli, config: &Config) -> Self {
-        let filestem = Metadata::determine_filestem(cli);
-        let author = Metadata::determine_author(cli, config);
+    /// Determine the extra metadata written to the document from the given field options and the config file.
+    /// - If the enrich option is provided, every field in the config file is written,
+    ///   with the value from the field options, the default value of the field or an empty value.
+    /// - The fields that are not labels in the config file are written after the labels.
+    /// - If the enrich option is not provided, only the fields are written.
+    fn determine_extra_values(options: &NewOptions, config: &Config) -> Vec<(String, String)> {
+        let mut extra_values: Vec<(String, String)> = vec![];
+        if options.enrich {
+            if let Some(rich) = &config.settings.rich {
+                for field in &rich.extra_metadata {
+                    let value = options
+                        .fields
+                        .iter()
+                        .rev()
+                        .find(|(key, _)| key == &field.label)
+                        .map(|(_, value)| value.clone())
+                        .or_else(|| field.default.clone())
+                        .unwrap_or_default();
+                    extra_values.push((field.label.clone(), value));
+                }
+            }
+        }
+        for (key, value) in &options.fields {
+            match extra_values.iter_mut().find(|(label, _)| label == key) {
+                Some(extra_value) => extra_value.1 = value.clone(),
+                None => extra_values.push((key.clone(), value.clone())),
+            }
+        }
+        extra_values
+    }
+
+    /// Create a new Metadata struct from the given NewOptions and Config.
+    ///
+    /// The template is expected to be already applied to the given Config.
+    /// A title that cannot be a filename, such as `..`, is an [`Error::InvalidFilename`],
+    /// and front matter for a format that writes the metadata in its own syntax, such as `.org`, is an [`Error::InvalidSetting`].
+    pub fn from(options: &NewOptions, config: &Config) -> Result<Self> {
+        let filestem = Metadata::determine_filestem(options)?;
+        let author = Metadata::determine_author(options, config);
         let datetime = Local::now();
-        let extension = Metadata::determine_extension(cli, config);
-        Metadata {
+        let extension = Metadata::determine_extension(options, config);
+        let template = Metadata::determine_template(options, config);
+        let extra_values = Metadata::determine_extra_values(options, config);
+        let front_matter = Metadata::determine_front_matter(options, config);
+        if front_matter != FrontMatter::None && !renderer::for_extension(extension.as_deref()).supports_front_matter() {
+            return Err(Error::InvalidSetting(format!(
+                "A .{} document cannot have front matter, so the front matter must be none",
+                extension.as_deref().unwrap_or_default()
+            )));
+        }
+        let output_dir = Metadata::determine_output_dir(options, config);
+        Ok(Metadata {
             filestem,
             author,
             datetime,
             extension,
-            header: config.header.clone(),
-            footer: config.footer.clone(),
-            extra_metadata: match &config.rich {
-                Some(rich) => Some(rich.extra_metadata.clone()),
-                None => None,
-            },
+            header: config.settings.header.clone(),
+            footer: config.settings.footer.clone(),
+            extra_metadata: config.settings.rich.as_ref().map(|rich| rich.extra_metadata.clone()),
+            extra_values,
+            sections: config.settings.sections.clone().map(|mut sections| {
+                Section::sort(&mut sections);
+                sections
+            }),
+            template,
+            front_matter,
+            output_dir,
+            filename: config.settings.filename_pattern.clone(),
+        })
+    }
+
+    /// Find the field of the extra metadata with the given label.
+    pub fn find_extra_field(&self, label: &str) -> Option<&ExtraField> {
+        self.extra_metadata
+            .as_ref()
+            .and_then(|fields| fields.iter().find(|field| field.label == label))
+    }
+
+    /// Validate the values of the extra metadata against the types of their fields
+    /// and replace them with the normalized values.
+    ///
+    /// Values without a field in the config file are written as they are.
+    /// A label of the standard metadata, such as `title`, is rejected.
+    pub fn validate_extra_values(&mut self) -> Result<()> {
+        let mut validated = Vec::with_capacity(self.extra_values.len());
+        for (label, value) in &self.extra_values {
+            if RESERVED_LABELS.contains(&label.as_str()) {
+                return Err(Error::InvalidField(format!(
+                    "\"{}\" is a standard metadata and cannot be an extra metadata label",
+                    label
+                )));
+            }
+            let value = match self.find_extra_field(label) {
+                Some(field) => field.validate(value).map_err(Error::InvalidField)?,
+                None => value.clone(),
+            };
+            validated.push((label.clone(), value));
         }
+        self.extra_values = validated;
+        Ok(())
     }
 
-    /// Write the metadata to the document.
+    /// Substitute the placeholders in the header, footer, section bodies, output directory and filename.
     ///
-    /// ### Warning
-    /// This function is not the same as `to_config_file`.
-    /// This function writes the metadata to the document with certain formatting not to the config file.
-    pub fn write_to_doc(&self, new_file: &mut File, cli: &Cli) -> Result<(), std::io::Error>{
-        new_file.write(
-            format!(
-                "{}",
-                match self.extension {
-                    Some(ref extension) => match extension.as_str() {
-                        "md" => "# ".to_string(),
-                        _ => "".to_string(),
-                    },
-                    None => "".to_string(),
-                }
-            )
-                .as_bytes(),
-        )?;
-        new_file.write(format!("{}\n\n", &self.filestem).as_bytes())?;
-        new_file.write(
-            format!(
-                "created: {}\n\
-    author: {}\n\n",
-                self.datetime.format("%Y-%m-%d %H:%M:%S"),
-                &self.author
-            )
-                .as_bytes(),
-        )?;
-
-        match cli.enrich {
-            true => {
-                match &self.extra_metadata {
-                    Some(extra_metadata) => {
-                        for metadata in extra_metadata {
-                            new_file.write(format!("{}: \n", metadata).as_bytes())?;
-                        }
-                        new_file.write("\n".as_bytes())?;
-                    }
-                    None => (),
+    /// The filestem is the title given by the user, so it is kept as it is, even if it has `{{`.
+    /// See [`placeholder`] for the supported variables.
+    pub fn render_placeholders(&mut self) -> Result<()> {
+        if let Some(header) = &self.header {
+            self.header = Some(placeholder::render(header, self)?);
+        }
+        if let Some(footer) = &self.footer {
+            self.footer = Some(placeholder::render(footer, self)?);
+        }
+        if let Some(sections) = &self.sections {
+            let mut rendered = sections.clone();
+            for section in rendered.iter_mut() {
+                if let Some(body) = &section.body {
+                    section.body = Some(placeholder::render(body, self)?);
                 }
             }
-            false => (),
+            self.sections = Some(rendered);
         }
+        if let Some(output_dir) = &self.output_dir {
+            self.output_dir = Some(placeholder::render(output_dir, self)?);
+        }
+        if let Some(pattern) = &self.filename {
+            let filename = placeholder::render(pattern, self)?;
+            // The filename is joined to the output directory, so it must not name another directory.
+            if filename.trim().is_empty()
+                || filename == "."
+                || filename == ".."
+                || filename.contains(['/', '\\'])
+            {
+                return Err(Error::InvalidFilename(filename));
+            }
+            self.filename = Some(filename);
+        }
+        Ok(())
+    }
 
-        new_file.write(
-            format!(
-                "{}\n",
-                match &self.header {
-                    Some(header) => header,
-                    None => "",
-                }
-            )
-                .as_bytes(),
-        )?;
+    /// The name of the file, which is the filename or the title with the extension.
+    pub fn file_name(&self) -> String {
+        let stem = self.filename.as_deref().unwrap_or(&self.filestem);
+        match &self.extension {
+            Some(extension) => format!("{}.{}", stem, extension),
+            None => stem.to_string(),
+        }
+    }
 
-        new_file.write("\n".as_bytes())?;
+    /// Write the document with the renderer of its extension. The writer can be a file or any other writer.
+    ///
+    /// See [`renderer`] for the formats.
+    pub fn write_to_doc<W: Write>(&self, new_file: &mut W) -> Result<()> {
+        renderer::for_extension(self.extension.as_deref()).render(self, new_file)
+    }
+
+    /// Render the document as [`Metadata::write_to_doc`] writes it.
+    pub fn to_doc_string(&self) -> Result<String> {
+        let mut document = vec![];
+        self.write_to_doc(&mut document)?;
+        String::from_utf8(document).map_err(|e| Error::Render(e.to_string()))
+    }
+}
 
-        new_file.write(
-            format!(
-                "{}",
-                match &self.footer {
-                    Some(footer) => footer,
-                    None => "",
-                }
-            )
-                .as_bytes(),
-        )?;
 
-        new_file.flush()?;
+impl fmt::Display for Metadata {
+    /// Format the metadata as `key: value` lines for showing it to the user.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "title: {}", self.filestem)?;
+        writeln!(f, "created: {}", self.datetime.format("%Y-%m-%d %H:%M:%S"))?;
+        writeln!(f, "author: {}", self.author)?;
+        if let Some(extension) = &self.extension {
+            writeln!(f, "extension: {}", extension)?;
+        }
+        for (label, value) in &self.extra_values {
+            writeln!(f, "{}: {}", label, value)?;
+        }
+        if let Some(sections) = &self.sections {
+            let headings: Vec<&str> = sections.iter().map(|section| section.heading.as_str()).collect();
+            writeln!(f, "sections: {}", headings.join(", "))?;
+        }
         Ok(())
     }
 }
 
-
 #[cfg(test)]
+#[allow(clippy::field_reassign_with_default)]
 mod tests {
     use super::*;
+    use crate::config::rich_metadata::{FieldType, RichMetadata};
+
+    #[test]
+    fn test_remember() {
+        let mut config = Config::new();
+        config.set_author("John Doe".to_string());
+        config.set_header(Some("header".to_string()));
+        let options = NewOptions {
+            title: "test".to_string(),
+            extension: Some("md".to_string()),
+            template: Some("standup".to_string()),
+            ..Default::default()
+        };
+        Metadata::remember(&options, &mut config);
+        assert_eq!(config.settings.author.as_deref(), Some("John Doe"));
+        assert_eq!(config.settings.extension.as_deref(), Some("md"));
+        assert_eq!(config.settings.default_template, None);
+        assert_eq!(config.settings.header.as_deref(), Some("header"));
+        assert_eq!(config.settings.front_matter, None);
+    }
 
     #[test]
     fn test_determine_filestem() {
-        let mut cli = Cli::default();
-        cli.filename = "test".to_string();
+        let mut options = NewOptions::default();
+        options.title = "test".to_string();
 
-        let filestem = Metadata::determine_filestem(&cli);
+        let filestem = Metadata::determine_filestem(&options).unwrap();
         assert_eq!(filestem, "test");
+        for title in ["..", "/", ""] {
+            options.title = title.to_string();
+            assert!(matches!(Metadata::determine_filestem(&options), Err(Error::InvalidFilename(_))));
+            assert!(Metadata::from(&options, &Config::new()).is_err());
+        }
     }
 
     #[test]
-    fn test_determine_extension_in_filename() {
-        let mut cli = Cli::default();
-        cli.filename = "test.txt".to_string();
+    fn test_determine_extension_in_options() {
+        let mut options = NewOptions::default();
+        options.title = "test".to_string();
+        options.extension = Some("txt".to_string());
 
         let config = Config::new();
-        let extension = Metadata::determine_extension(&cli, &config);
+        let extension = Metadata::determine_extension(&options, &config);
         assert!(extension.is_some_and(|ext| ext == "txt"));
     }
 
     #[test]
     fn test_determine_extension_is_none() {
-        let mut cli = Cli::default();
-        cli.filename = "test".to_string();
+        let mut options = NewOptions::default();
+        options.title = "test".to_string();
 
         let config = Config::new();
-        let extension = Metadata::determine_extension(&cli, &config);
+        let extension = Metadata::determine_extension(&options, &config);
         assert!(extension.is_none());
     }
 
     #[test]
     fn test_determine_extension_in_config() {
-        let mut cli = Cli::default();
-        cli.filename = "test".to_string();
+        let mut options = NewOptions::default();
+        options.title = "test".to_string();
 
         let mut config = Config::new();
-        config.extension = Some("md".to_string());
-        let extension = Metadata::determine_extension(&cli, &config);
+        config.settings.extension = Some("md".to_string());
+        let extension = Metadata::determine_extension(&options, &config);
         assert!(extension.is_some_and(|ext| ext == "md"));
     }
 
     #[test]
-    fn test_determine_extension_in_filename_and_config() {
-        let mut cli = Cli::default();
-        cli.filename = "test.md".to_string();
+    fn test_determine_extension_in_options_and_config() {
+        let mut options = NewOptions::default();
+        options.title = "test".to_string();
+        options.extension = Some("md".to_string());
 
         let mut config = Config::new();
-        config.extension = Some("txt".to_string());
-        let extension = Metadata::determine_extension(&cli, &config);
+        config.settings.extension = Some("txt".to_string());
+        let extension = Metadata::determine_extension(&options, &config);
         assert!(extension.is_some_and(|ext| ext == "md"));
     }
 
     #[test]
     fn test_determine_author_in_cli() {
-        let mut cli = Cli::default();
-        cli.filename = "test".to_string();
-        cli.author = Option::from("testuser".to_string());
+        let mut options = NewOptions::default();
+        options.title = "test".to_string();
+        options.author = Option::from("testuser".to_string());
 
         let config = Config::new();
-        let author = Metadata::determine_author(&cli, &config);
+        let author = Metadata::determine_author(&options, &config);
         assert_eq!(author, "testuser");
     }
 
     #[test]
     fn test_determine_author_in_config() {
-        let mut cli = Cli::default();
-        cli.filename = "test".to_string();
-        cli.author = None;
+        let mut options = NewOptions::default();
+        options.title = "test".to_string();
+        options.author = None;
 
         let mut config = Config::new();
         config.set_author("testuser".to_string());
-        let author = Metadata::determine_author(&cli, &config);
+        let author = Metadata::determine_author(&options, &config);
         assert_eq!(author, "testuser");
     }
 
     #[test]
     fn test_determine_author_in_cli_and_config() {
-        let mut cli = Cli::default();
-        cli.filename = "test".to_string();
-        cli.author = Option::from("cliuser".to_string());
+        let mut options = NewOptions::default();
+        options.title = "test".to_string();
+        options.author = Option::from("cliuser".to_string());
 
         let mut config = Config::new();
         config.set_author("configuser".to_string());
-        let author = Metadata::determine_author(&cli, &config);
+        let author = Metadata::determine_author(&options, &config);
         assert_eq!(author, "cliuser");
     }
 
     #[test]
     fn test_determine_author_whoami() {
-        let mut cli = Cli::default();
-        cli.filename = "test".to_string();
-        cli.author = None;
+        let mut options = NewOptions::default();
+        options.title = "test".to_string();
+        options.author = None;
 
         let config = Config::new();
-        let author = Metadata::determine_author(&cli, &config);
+        let author = Metadata::determine_author(&options, &config);
         assert_eq!(author, whoami::username());
     }
 
+    #[test]
+    fn test_determine_template_in_cli_and_config() {
+        let mut options = NewOptions::default();
+        options.template = Some("retro".to_string());
+
+        let mut config = Config::new();
+        config.set_default_template(Some("standup".to_string()));
+        assert_eq!(Metadata::determine_template(&options, &config).unwrap(), "retro");
+    }
+
+    #[test]
+    fn test_determine_template_in_config() {
+        let options = NewOptions::default();
+
+        let mut config = Config::new();
+        config.set_default_template(Some("standup".to_string()));
+        assert_eq!(Metadata::determine_template(&options, &config).unwrap(), "standup");
+    }
+
+    #[test]
+    fn test_determine_extra_values_with_enrich() {
+        let mut options = NewOptions::default();
+        options.enrich = true;
+        options.fields = vec![
+            ("agenda".to_string(), "Budget".to_string()),
+            ("room".to_string(), "301".to_string()),
+        ];
+
+        let mut config = Config::new();
+        config.set_rich(RichMetadata {
+            extra_metadata: vec!["location".into(), "agenda".into()],
+        });
+        assert_eq!(
+            Metadata::determine_extra_values(&options, &config),
+            vec![
+                ("location".to_string(), "".to_string()),
+                ("agenda".to_string(), "Budget".to_string()),
+                ("room".to_string(), "301".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_determine_extra_values_without_enrich() {
+        let mut options = NewOptions::default();
+        options.fields = vec![("agenda".to_string(), "Budget".to_string())];
+
+        let mut config = Config::new();
+        config.set_rich(RichMetadata {
+            extra_metadata: vec!["location".into(), "agenda".into()],
+        });
+        assert_eq!(
+            Metadata::determine_extra_values(&options, &config),
+            vec![("agenda".to_string(), "Budget".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_determine_extra_values_with_default() {
+        let mut options = NewOptions::default();
+        options.enrich = true;
+
+        let mut config = Config::new();
+        config.set_rich(RichMetadata {
+            extra_metadata: vec![ExtraField {
+                default: Some("Seoul".to_string()),
+                ..ExtraField::from("location")
+            }],
+        });
+        assert_eq!(
+            Metadata::determine_extra_values(&options, &config),
+            vec![("location".to_string(), "Seoul".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_validate_extra_values() {
+        let mut metadata = Metadata {
+            extra_metadata: Some(vec![ExtraField {
+                field_type: FieldType::People,
+                ..ExtraField::from("attendees")
+            }]),
+            extra_values: vec![
+                ("attendees".to_string(), "John,Jane".to_string()),
+                ("room".to_string(), "301".to_string()),
+            ],
+            ..Default::default()
+        };
+        metadata.validate_extra_values().unwrap();
+        assert_eq!(
+            metadata.extra_values,
+            vec![
+                ("attendees".to_string(), "John, Jane".to_string()),
+                ("room".to_string(), "301".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_validate_extra_values_with_invalid_value() {
+        let mut metadata = Metadata {
+            extra_metadata: Some(vec![ExtraField {
+                field_type: FieldType::Date,
+                ..ExtraField::from("due")
+            }]),
+            extra_values: vec![("due".to_string(), "tomorrow".to_string())],
+            ..Default::default()
+        };
+        assert!(metadata.validate_extra_values().is_err());
+    }
+
+    #[test]
+    fn test_validate_extra_values_with_reserved_label() {
+        for label in RESERVED_LABELS {
+            let mut metadata = Metadata {
+                extra_values: vec![(label.to_string(), "value".to_string())],
+                ..Default::default()
+            };
+            assert!(matches!(metadata.validate_extra_values(), Err(Error::InvalidField(_))));
+        }
+    }
+
     #[test]
     fn test_metadata_from() {
-        let mut cli = Cli::default();
-        cli.filename = "test".to_string();
-        cli.author = Option::from("testuser".to_string());
+        let mut options = NewOptions::default();
+        options.title = "test".to_string();
+        options.author = Option::from("testuser".to_string());
 
         let mut config = Config::new();
-        config.extension = Some("md".to_string());
+        config.settings.extension = Some("md".to_string());
         config.set_author("configuser".to_string());
-        let metadata = Metadata::from(&cli, &config);
+        let metadata = Metadata::from(&options, &config).unwrap();
         assert_eq!(metadata.filestem, "test");
         assert_eq!(metadata.author, "testuser");
         assert!(metadata.extension.is_some_and(|ext| ext == "md"));
     }
+
+    #[test]
+    fn test_metadata_from_sorts_sections() {
+        let mut options = NewOptions::default();
+        options.title = "test".to_string();
+
+        let mut config = Config::new();
+        config.set_sections(Some(vec![
+            Section::new("Next Steps"),
+            Section {
+                order: Some(1),
+                ..Section::new("Action Items")
+            },
+        ]));
+        let metadata = Metadata::from(&options, &config).unwrap();
+        let headings: Vec<String> = metadata
+            .sections
+            .unwrap()
+            .into_iter()
+            .map(|section| section.heading)
+            .collect();
+        assert_eq!(headings, vec!["Action Items", "Next Steps"]);
+    }
+
+    #[test]
+    fn test_file_name() {
+        let mut metadata = Metadata {
+            filestem: "Weekly Sync".to_string(),
+            extension: Some("md".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(metadata.file_name(), "Weekly Sync.md");
+        metadata.filename = Some("{{date}}_{{slug title}}".to_string());
+        metadata.render_placeholders().unwrap();
+        assert_eq!(
+            metadata.file_name(),
+            format!("{}_weekly-sync.md", metadata.datetime.format("%Y-%m-%d"))
+        );
+        assert_eq!(metadata.filestem, "Weekly Sync");
+
+        metadata.filename = Some("{{slug title}}".to_string());
+        metadata.filestem = "???".to_string();
+        assert!(matches!(metadata.render_placeholders(), Err(Error::InvalidFilename(_))));
+    }
+
+    #[test]
+    fn test_determine_output_dir() {
+        let mut config = Config::new();
+        let mut options = NewOptions::default();
+        assert_eq!(Metadata::determine_output_dir(&options, &config), None);
+        config.set_output_dir(Some("minutes/{{year}}".to_string()));
+        assert_eq!(Metadata::determine_output_dir(&options, &config).as_deref(), Some("minutes/{{year}}"));
+        options.output_dir = Some("archive".to_string());
+        assert_eq!(Metadata::determine_output_dir(&options, &config).as_deref(), Some("archive"));
+    }
+
+    #[test]
+    fn test_determine_front_matter() {
+        let mut options = NewOptions::default();
+        let mut config = Config::new();
+        assert_eq!(Metadata::determine_front_matter(&options, &config), FrontMatter::None);
+
+        config.set_front_matter(Some(FrontMatter::Yaml));
+        assert_eq!(Metadata::determine_front_matter(&options, &config), FrontMatter::Yaml);
+
+        options.front_matter = Some(FrontMatter::None);
+        assert_eq!(Metadata::determine_front_matter(&options, &config), FrontMatter::None);
+    }
+
+    #[test]
+    fn test_from_with_front_matter_for_org() {
+        let mut options = NewOptions::default();
+        options.title = "notes".to_string();
+        options.extension = Some("org".to_string());
+        options.front_matter = Some(FrontMatter::Yaml);
+        let config = Config::new();
+        assert!(matches!(Metadata::from(&options, &config), Err(Error::InvalidSetting(_))));
+        options.front_matter = Some(FrontMatter::None);
+        assert_eq!(Metadata::from(&options, &config).unwrap().extension.as_deref(), Some("org"));
+    }
+
+    #[test]
+    fn test_write_to_doc_with_front_matter() {
+        let metadata = Metadata {
+            filestem: "test".to_string(),
+            author: "testuser".to_string(),
+            extension: Some("md".to_string()),
+            header: Some("header".to_string()),
+            extra_values: vec![("location".to_string(), "Seoul".to_string())],
+            front_matter: FrontMatter::Yaml,
+            ..Default::default()
+        };
+        let mut document = vec![];
+        metadata.write_to_doc(&mut document).unwrap();
+        assert_eq!(
+            String::from_utf8(document).unwrap(),
+            format!(
+                "---\ntitle: test\ncreated: {}\nauthor: testuser\nlocation: Seoul\n---\n\n\
+                # test\n\nheader\n\n",
+                metadata.datetime.to_rfc3339()
+            )
+        );
+    }
+
+    #[test]
+    fn test_display() {
+        let metadata = Metadata {
+            filestem: "test".to_string(),
+            author: "testuser".to_string(),
+            extension: Some("md".to_string()),
+            extra_values: vec![("location".to_string(), "Seoul".to_string())],
+            sections: Some(vec![Section::new("Action Items"), Section::new("Next Steps")]),
+            ..Default::default()
+        };
+        assert_eq!(
+            metadata.to_string(),
+            format!(
+                "title: test\ncreated: {}\nauthor: testuser\nextension: md\n\
+                location: Seoul\nsections: Action Items, Next Steps\n",
+                metadata.datetime.format("%Y-%m-%d %H:%M:%S")
+            )
+        );
+    }
+
+    #[test]
+    fn test_render_placeholders() {
+        let mut metadata = Metadata {
+            filestem: "Weekly sync".to_string(),
+            author: "testuser".to_string(),
+            header: Some("{{title}} by {{author}}".to_string()),
+            footer: Some("end of {{title}}".to_string()),
+            sections: Some(vec![Section {
+                body: Some("- [ ] {{author}}".to_string()),
+                ..Section::new("Action Items")
+            }]),
+            output_dir: Some("minutes/{{year}}/{{month}}".to_string()),
+            ..Default::default()
+        };
+        let title = "Weekly sync";
+        let output_dir = format!("minutes/{}", metadata.datetime.format("%Y/%m"));
+        metadata.render_placeholders().unwrap();
+        assert_eq!(metadata.output_dir, Some(output_dir));
+        assert_eq!(metadata.filestem, title);
+        assert_eq!(metadata.header.unwrap(), format!("{} by testuser", title));
+        assert_eq!(metadata.footer.unwrap(), format!("end of {}", title));
+        assert_eq!(
+            metadata.sections.unwrap()[0].body.as_deref(),
+            Some("- [ ] testuser")
+        );
+    }
+
+    #[test]
+    fn test_render_placeholders_keeps_title() {
+        for title in ["Plan {{ x", "t {{env.HOME}}"] {
+            let mut metadata = Metadata {
+                filestem: title.to_string(),
+                header: Some("{{title}}".to_string()),
+                ..Default::default()
+            };
+            metadata.render_placeholders().unwrap();
+            assert_eq!(metadata.filestem, title);
+            assert_eq!(metadata.header.as_deref(), Some(title));
+        }
+    }
+
+    #[test]
+    fn test_render_placeholders_with_unknown_variable() {
+        let mut metadata = Metadata {
+            header: Some("{{unknown}}".to_string()),
+            ..Default::default()
+        };
+        assert!(metadata.render_placeholders().is_err());
+    }
+
+    #[test]
+    fn test_render_placeholders_with_invalid_filename() {
+        for (title, pattern) in [("a/b", "{{title}}"), ("a\\b", "{{title}}"), ("x", ".."), ("x", "."), ("x", "../{{title}}")] {
+            let mut metadata = Metadata {
+                filestem: title.to_string(),
+                filename: Some(pattern.to_string()),
+                ..Default::default()
+            };
+            assert!(
+                matches!(metadata.render_placeholders(), Err(Error::InvalidFilename(_))),
+                "{} with {}",
+                title,
+                pattern
+            );
+        }
+    }
+
+    #[test]
+    fn test_write_to_doc_with_sections() {
+        let mut metadata = Metadata {
+            filestem: "test".to_string(),
+            author: "testuser".to_string(),
+            header: Some("header".to_string()),
+            footer: Some("footer".to_string()),
+            extra_values: vec![("location".to_string(), "Seoul".to_string())],
+            sections: Some(vec![
+                Section {
+                    body: Some("- [ ] ".to_string()),
+                    ..Section::new("Action Items")
+                },
+                Section::new("Decisions Made"),
+            ]),
+            ..Default::default()
+        };
+        let written = |metadata: &Metadata| metadata.to_doc_string().unwrap();
+
+        metadata.extension = Some("md".to_string());
+        let created = metadata.datetime.format("%Y-%m-%d %H:%M:%S");
+        assert_eq!(
+            written(&metadata),
+            format!(
+                "# test\n\ncreated: {}\nauthor: testuser\n\nlocation: Seoul\n\nheader\n\n\
+                ## Action Items\n\n- [ ] \n\n\
+                ## Decisions Made\n\n\
+                footer",
+                created
+            )
+        );
+
+        metadata.extension = Some("txt".to_string());
+        assert_eq!(
+            written(&metadata),
+            format!(
+                "test\n\ncreated: {}\nauthor: testuser\n\nlocation: Seoul\n\nheader\n\n\
+                Action Items\n------------\n\n- [ ] \n\n\
+                Decisions Made\n--------------\n\n\
+                footer",
+                created
+            )
+        );
+    }
 }
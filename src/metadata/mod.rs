@@ -2,4 +2,13 @@
 //!
 //!
 
+#[allow(clippy::module_inception)]
 pub mod metadata;
+pub mod builder;
+pub mod front_matter;
+pub mod options;
+pub mod parser;
+pub mod placeholder;
+pub mod prompt;
+pub mod renderer;
+pub mod state;
@@ -0,0 +1,56 @@
+//! The values of a new document given by the user, such as on the command line or to [`MomBuilder`](super::builder::MomBuilder).
+//!
+//! They take precedence over the [`Config`](crate::config::Config) when the [`Metadata`](super::metadata::Metadata) is created.
+//!
+
+use crate::config::front_matter::FrontMatter;
+use crate::error::{Error, Result};
+
+/// The values of a new document given by the user. A value that is not given is taken from the config.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct NewOptions {
+    /// The title of the document, which is also its filename unless the config has a `filename_pattern`
+    pub title: String,
+    /// The extension of the document, without the dot
+    pub extension: Option<String>,
+    /// The author of the document
+    pub author: Option<String>,
+    /// Write every extra metadata of the config, not only the given fields
+    pub enrich: bool,
+    /// The labels and values of the extra metadata
+    pub fields: Vec<(String, String)>,
+    /// The name of the template to use
+    pub template: Option<String>,
+    /// The format of the front matter
+    pub front_matter: Option<FrontMatter>,
+    /// The directory the document is written to, which can have placeholders such as `minutes/{{year}}`
+    pub output_dir: Option<String>,
+}
+
+impl NewOptions {
+    /// Check that the title can be used as a filename: it is not empty, `.` or `..`, and has no `/` or `\`.
+    pub fn validate_title(&self) -> Result<()> {
+        let title = self.title.trim();
+        match title.is_empty() || title == "." || title == ".." || title.contains(['/', '\\']) {
+            true => Err(Error::InvalidFilename(self.title.clone())),
+            false => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_title() {
+        for title in ["Weekly sync", "v1.2 release", "주간 회의"] {
+            let options = NewOptions { title: title.to_string(), ..Default::default() };
+            assert!(options.validate_title().is_ok(), "{}", title);
+        }
+        for title in ["", " ", ".", "..", "a/b", "a\\b"] {
+            let options = NewOptions { title: title.to_string(), ..Default::default() };
+            assert!(matches!(options.validate_title(), Err(Error::InvalidFilename(_))), "{}", title);
+        }
+    }
+}
@@ -0,0 +1,639 @@
+//! Parser that reads a document written by momi back into [`Metadata`].
+//!
+//! The parser understands every layout [`Metadata::write_to_doc`] writes.
+//!
+//! The layout of Markdown and plain text documents is:
+//!
+//! - The title, with the `# ` prefix of Markdown documents
+//! - The `created: ...` and `author: ...` lines followed by the `label: value` lines of the extra metadata
+//! - The YAML(`---`) or TOML(`+++`) front matter instead of those lines
+//! - The header, the sections with their bodies, and the footer
+//!
+//! Org-mode, AsciiDoc and reStructuredText documents start with the title and the metadata in their own syntax,
+//! as described in [`renderer`](super::renderer), followed by the same header, sections and footer.
+//! The label of an extra metadata is read as the name of its keyword or attribute, such as `meeting_chair`
+//! for `#+MEETING_CHAIR:` and `meeting-chair` for `:meeting-chair:`, which is written back to the same name.
+//!
+//! Writing the parsed metadata again yields the same bytes as the parsed document,
+//! except that Windows line endings(`\r\n`) are read and written as `\n`.
+//!
+//! The layout has some ambiguities that are resolved as follows:
+//! - A block of `label: value` lines right after the `author` line is read as the extra metadata, not as the header.
+//! - Sections are `## ` headings in `.md`, `* ` headings in `.org`, `== ` headings in `.adoc`
+//!   and underlined headings in the others, which must follow a blank line.
+//! - Without sections, the header ends at the first blank line and the rest is the footer.
+//! - With sections, the footer is the text after the last blank line of the last section.
+//!
+
+use std::error::Error;
+use std::fs;
+use std::path::Path;
+use chrono::{DateTime, Local, NaiveDateTime, TimeZone};
+use crate::config::front_matter::FrontMatter;
+use crate::config::rich_metadata::{ExtraField, FieldType};
+use crate::config::section::Section;
+use crate::error::Result;
+use crate::metadata::metadata::Metadata;
+use crate::metadata::renderer::display_width;
+
+/// The format of the `created` line.
+const CREATED_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+/// Parse the document at the given path.
+/// The extension of the path decides how the title and the section headings are read.
+pub fn parse_file<P: AsRef<Path>>(path: P) -> Result<Metadata> {
+    let text = fs::read_to_string(&path)?;
+    let extension = path.as_ref().extension().and_then(|ext| ext.to_str());
+    parse(&text, extension)
+}
+
+/// Parse the given text of a document.
+///
+/// If the extension is not given, the document is read as Markdown if its title starts with `# `.
+/// Windows line endings are read as `\n`, so a document edited on Windows can be read back.
+pub fn parse(text: &str, extension: Option<&str>) -> Result<Metadata> {
+    let text = text.replace("\r\n", "\n");
+    parse_document(&text, extension).map_err(|e| crate::Error::DocumentParse(e.to_string()))
+}
+
+fn parse_document(text: &str, extension: Option<&str>) -> std::result::Result<Metadata, Box<dyn Error>> {
+    let mut metadata = Metadata::default();
+    let rest = match extension {
+        Some("org") => read_org_header(text, &mut metadata)?,
+        Some("adoc") => read_asciidoc_header(text, &mut metadata)?,
+        Some("rst") => read_rst_header(text, &mut metadata)?,
+        _ => read_text_header(text, extension, &mut metadata)?,
+    };
+    if metadata.extension.is_none() {
+        metadata.extension = extension.map(|extension| extension.to_string());
+    }
+    read_body(rest, &mut metadata);
+    Ok(metadata)
+}
+
+/// Read the front matter or the title and the metadata lines of a Markdown or plain text document,
+/// and return the rest of the document.
+fn read_text_header<'a>(
+    text: &'a str,
+    extension: Option<&str>,
+    metadata: &mut Metadata,
+) -> std::result::Result<&'a str, Box<dyn Error>> {
+    let mut rest = text;
+
+    for front_matter in [FrontMatter::Yaml, FrontMatter::Toml] {
+        let delimiter = front_matter.delimiter().unwrap_or_default();
+        if let Some(after_open) = rest.strip_prefix(&format!("{}\n", delimiter)) {
+            let close = format!("{}\n", delimiter);
+            let end = find_line(after_open, &close).ok_or("Front matter is not closed")?;
+            read_front_matter(&after_open[..end], front_matter, metadata)?;
+            rest = skip_blank_line(&after_open[end + close.len()..])?;
+            metadata.front_matter = front_matter;
+            break;
+        }
+    }
+
+    let (title, after_title) = rest.split_once('\n').ok_or("Document has no title")?;
+    metadata.extension = match extension {
+        Some(extension) => Some(extension.to_string()),
+        None if title.starts_with("# ") => Some("md".to_string()),
+        None => None,
+    };
+    metadata.filestem = match metadata.extension.as_deref() {
+        Some("md") => title.strip_prefix("# ").unwrap_or(title),
+        _ => title,
+    }
+    .to_string();
+    rest = skip_blank_line(after_title)?;
+
+    if metadata.front_matter == FrontMatter::None {
+        let (block, after_block) = rest.split_once("\n\n").ok_or("Document has no metadata")?;
+        for line in block.lines() {
+            match line.split_once(": ") {
+                Some(("created", created)) => metadata.datetime = local_datetime(created, CREATED_FORMAT)?,
+                Some(("author", author)) => metadata.author = author.to_string(),
+                _ => return Err(format!("Unexpected metadata line \"{}\"", line).into()),
+            }
+        }
+        rest = after_block;
+
+        if let Some((block, after_block)) = rest.split_once("\n\n") {
+            let extra_values: Option<Vec<(String, String)>> = block
+                .split('\n')
+                .map(|line| {
+                    line.split_once(": ")
+                        .map(|(label, value)| (label.to_string(), value.to_string()))
+                })
+                .collect();
+            if let Some(extra_values) = extra_values.filter(|_| !block.is_empty()) {
+                metadata.extra_values = extra_values;
+                rest = after_block;
+            }
+        }
+    }
+
+    Ok(rest)
+}
+
+/// Read the `#+TITLE:`, `#+AUTHOR:`, `#+DATE:` and other keywords of an Org-mode document,
+/// and return the rest of the document.
+fn read_org_header<'a>(text: &'a str, metadata: &mut Metadata) -> std::result::Result<&'a str, Box<dyn Error>> {
+    let (block, rest) = text.split_once("\n\n").ok_or("Document has no metadata")?;
+    for line in block.lines() {
+        let (keyword, value) = line
+            .strip_prefix("#+")
+            .and_then(|line| line.split_once(": "))
+            .ok_or_else(|| format!("Unexpected keyword line \"{}\"", line))?;
+        match keyword {
+            "TITLE" => metadata.filestem = value.to_string(),
+            "AUTHOR" => metadata.author = value.to_string(),
+            "DATE" => metadata.datetime = local_datetime(value, "<%Y-%m-%d %a %H:%M>")?,
+            _ => metadata.extra_values.push((keyword.to_lowercase(), value.to_string())),
+        }
+    }
+    Ok(rest)
+}
+
+/// Read the `= ` title and the `:author:`, `:revdate:` and other attributes of an AsciiDoc document,
+/// and return the rest of the document.
+fn read_asciidoc_header<'a>(text: &'a str, metadata: &mut Metadata) -> std::result::Result<&'a str, Box<dyn Error>> {
+    let (block, rest) = text.split_once("\n\n").ok_or("Document has no metadata")?;
+    let mut lines = block.lines();
+    let title = lines.next().and_then(|line| line.strip_prefix("= ")).ok_or("Document has no title")?;
+    metadata.filestem = title.to_string();
+    for line in lines {
+        let (name, value) = line
+            .strip_prefix(':')
+            .and_then(|line| line.split_once(": "))
+            .ok_or_else(|| format!("Unexpected attribute line \"{}\"", line))?;
+        match name {
+            "author" => metadata.author = value.to_string(),
+            "revdate" => metadata.datetime = local_datetime(value, CREATED_FORMAT)?,
+            _ => metadata.extra_values.push((name.to_string(), value.to_string())),
+        }
+    }
+    Ok(rest)
+}
+
+/// Read the title between `=` lines and the `:Author:`, `:Date:` and other fields of a reStructuredText document,
+/// and return the rest of the document.
+fn read_rst_header<'a>(text: &'a str, metadata: &mut Metadata) -> std::result::Result<&'a str, Box<dyn Error>> {
+    let (overline, rest) = text.split_once('\n').ok_or("Document has no title")?;
+    let (title, rest) = rest.split_once('\n').ok_or("Document has no title")?;
+    let rest = rest.strip_prefix(&format!("{}\n", overline)).ok_or("Title is not underlined like its overline")?;
+    if overline.is_empty() || !overline.chars().all(|c| c == '=') {
+        return Err("Title has no overline".into());
+    }
+    metadata.filestem = title.to_string();
+    let (block, rest) = skip_blank_line(rest)?.split_once("\n\n").ok_or("Document has no metadata")?;
+    for line in block.lines() {
+        let (name, value) = rst_field(line).ok_or_else(|| format!("Unexpected field line \"{}\"", line))?;
+        match name.as_str() {
+            "Author" => metadata.author = value.to_string(),
+            "Date" => metadata.datetime = local_datetime(value, CREATED_FORMAT)?,
+            _ => metadata.extra_values.push((name, value.to_string())),
+        }
+    }
+    Ok(rest)
+}
+
+/// Split a `:name: value` field line of reStructuredText into its unescaped name and its value.
+fn rst_field(line: &str) -> Option<(String, &str)> {
+    let mut name = String::new();
+    let mut chars = line.strip_prefix(':')?.char_indices();
+    while let Some((index, c)) = chars.next() {
+        match c {
+            '\\' => name.push(chars.next()?.1),
+            ':' => return Some((name, line[index + 1..].strip_prefix(": ")?)),
+            c => name.push(c),
+        }
+    }
+    None
+}
+
+/// Parse the given local datetime in the given format.
+fn local_datetime(text: &str, format: &str) -> std::result::Result<DateTime<Local>, Box<dyn Error>> {
+    let datetime = NaiveDateTime::parse_from_str(text, format)?;
+    Ok(Local.from_local_datetime(&datetime).earliest().ok_or("Invalid created datetime")?)
+}
+
+/// Find the byte offset of the given line in the text.
+fn find_line(text: &str, line: &str) -> Option<usize> {
+    match text.starts_with(line) {
+        true => Some(0),
+        false => text.find(&format!("\n{}", line)).map(|offset| offset + 1),
+    }
+}
+
+/// Skip the blank line at the start of the text.
+fn skip_blank_line(text: &str) -> std::result::Result<&str, Box<dyn Error>> {
+    Ok(text.strip_prefix('\n').ok_or("Expected a blank line")?)
+}
+
+/// Read the title, created datetime, author and extra metadata from the given front matter.
+fn read_front_matter(body: &str, front_matter: FrontMatter, metadata: &mut Metadata) -> std::result::Result<(), Box<dyn Error>> {
+    let entries: Vec<(String, serde_json::Value)> = match front_matter {
+        FrontMatter::Yaml => serde_yaml::from_str::<serde_yaml::Mapping>(body)?
+            .into_iter()
+            .map(|(key, value)| Ok((yaml_key(key)?, serde_yaml::from_value(value)?)))
+            .collect::<std::result::Result<_, Box<dyn Error>>>()?,
+        FrontMatter::Toml => toml::from_str::<toml::Table>(body)?
+            .into_iter()
+            .map(|(key, value)| Ok((key, serde_json::to_value(value)?)))
+            .collect::<std::result::Result<_, Box<dyn Error>>>()?,
+        FrontMatter::None => vec![],
+    };
+    let mut fields = vec![];
+    for (key, value) in entries {
+        match (key.as_str(), value) {
+            ("title", serde_json::Value::String(title)) => metadata.filestem = title,
+            ("author", serde_json::Value::String(author)) => metadata.author = author,
+            ("created", serde_json::Value::String(created)) => {
+                metadata.datetime = DateTime::parse_from_rfc3339(&created)?.with_timezone(&Local);
+            }
+            (_, value) => {
+                let (value, field_type) = match value {
+                    serde_json::Value::Array(people) => (
+                        people
+                            .iter()
+                            .map(|person| person.as_str().map(|person| person.to_string()).unwrap_or(person.to_string()))
+                            .collect::<Vec<String>>()
+                            .join(", "),
+                        FieldType::People,
+                    ),
+                    serde_json::Value::Bool(boolean) => (boolean.to_string(), FieldType::Boolean),
+                    serde_json::Value::Number(number) => (
+                        match number.as_i64() {
+                            Some(integer) => integer.to_string(),
+                            None => format!("{:?}", number.as_f64().unwrap_or_default()),
+                        },
+                        FieldType::Number,
+                    ),
+                    serde_json::Value::String(text) => (text, FieldType::Text),
+                    serde_json::Value::Null => (String::new(), FieldType::Text),
+                    value => (value.to_string(), FieldType::Text),
+                };
+                if field_type != FieldType::Text {
+                    fields.push(ExtraField {
+                        field_type,
+                        ..ExtraField::from(key.as_str())
+                    });
+                }
+                metadata.extra_values.push((key, value));
+            }
+        }
+    }
+    if !fields.is_empty() {
+        metadata.extra_metadata = Some(fields);
+    }
+    Ok(())
+}
+
+/// Convert a key of the YAML front matter to a string.
+fn yaml_key(key: serde_yaml::Value) -> std::result::Result<String, Box<dyn Error>> {
+    match key {
+        serde_yaml::Value::String(key) => Ok(key),
+        key => Ok(serde_yaml::to_string(&key)?.trim_end().to_string()),
+    }
+}
+
+/// A section heading found in the body of the document.
+struct Heading {
+    /// The text of the heading.
+    text: String,
+    /// The byte offset where the heading starts.
+    start: usize,
+    /// The byte offset right after the heading.
+    end: usize,
+}
+
+/// Find the section headings in the given body of the document.
+fn find_headings(body: &str, extension: Option<&str>) -> Vec<Heading> {
+    let mut lines = vec![];
+    let mut offset = 0;
+    for line in body.split_inclusive('\n') {
+        lines.push((offset, line));
+        offset += line.len();
+    }
+    let mut headings = vec![];
+    for (index, (start, line)) in lines.iter().enumerate() {
+        if !body[..*start].ends_with("\n\n") || !line.ends_with('\n') {
+            continue;
+        }
+        let text = line.trim_end_matches('\n');
+        let prefix = match extension {
+            Some("md") => Some("## "),
+            Some("org") => Some("* "),
+            Some("adoc") => Some("== "),
+            _ => None,
+        };
+        match prefix {
+            Some(prefix) => {
+                if let Some(heading) = text.strip_prefix(prefix) {
+                    headings.push(Heading {
+                        text: heading.to_string(),
+                        start: *start,
+                        end: start + line.len(),
+                    });
+                }
+            }
+            _ => {
+                let underline = format!("{}\n", "-".repeat(display_width(text)));
+                if let Some((underline_start, next)) = lines.get(index + 1) {
+                    if !text.is_empty() && !text.chars().all(|c| c == '-') && *next == underline {
+                        headings.push(Heading {
+                            text: text.to_string(),
+                            start: *start,
+                            end: underline_start + next.len(),
+                        });
+                    }
+                }
+            }
+        }
+    }
+    headings
+}
+
+/// Read the header, the sections and the footer from the given body of the document.
+fn read_body(body: &str, metadata: &mut Metadata) {
+    let headings = find_headings(body, metadata.extension.as_deref());
+    let non_empty = |text: &str| match text.is_empty() {
+        true => None,
+        false => Some(text.to_string()),
+    };
+    let Some(first) = headings.first() else {
+        let (header, footer) = body.split_once("\n\n").unwrap_or((body, ""));
+        metadata.header = non_empty(header);
+        metadata.footer = non_empty(footer);
+        return;
+    };
+    let header = &body[..first.start];
+    metadata.header = non_empty(header.strip_suffix("\n\n").unwrap_or(header));
+
+    let mut sections = vec![];
+    for (index, heading) in headings.iter().enumerate() {
+        let region = &body[heading.end..];
+        let region = region.strip_prefix('\n').unwrap_or(region);
+        let section_body = match headings.get(index + 1) {
+            Some(next) => {
+                let region = &region[..next.start - (body.len() - region.len())];
+                non_empty(region.strip_suffix("\n\n").unwrap_or(region))
+            }
+            None => match region.rfind("\n\n") {
+                Some(end) => {
+                    metadata.footer = non_empty(&region[end + 2..]);
+                    Some(region[..end].to_string())
+                }
+                None => {
+                    metadata.footer = non_empty(region);
+                    None
+                }
+            },
+        };
+        sections.push(Section {
+            body: section_body,
+            ..Section::new(&heading.text)
+        });
+    }
+    metadata.sections = Some(sections);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::File;
+    use tempfile::tempdir;
+
+    /// Write the given metadata to a document and return its text.
+    fn write(metadata: &Metadata) -> String {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("document");
+        let mut file = File::create(&path).unwrap();
+        metadata.write_to_doc(&mut file).unwrap();
+        fs::read_to_string(&path).unwrap()
+    }
+
+    /// Assert that writing, reading and writing the given metadata yields the same bytes.
+    fn assert_round_trip(metadata: &Metadata) -> Metadata {
+        let written = write(metadata);
+        let parsed = parse(&written, metadata.extension.as_deref()).unwrap();
+        assert_eq!(write(&parsed), written);
+        parsed
+    }
+
+    fn metadata(extension: &str) -> Metadata {
+        Metadata {
+            filestem: "Weekly sync".to_string(),
+            author: "John Doe".to_string(),
+            datetime: Local.with_ymd_and_hms(2024, 5, 2, 9, 30, 0).unwrap(),
+            extension: Some(extension.to_string()),
+            header: Some("--------header--------".to_string()),
+            footer: Some("--------footer--------".to_string()),
+            extra_values: vec![
+                ("location".to_string(), "Room 301".to_string()),
+                ("agenda".to_string(), "".to_string()),
+            ],
+            sections: Some(vec![
+                Section {
+                    body: Some("- [ ] Send the report\n- [ ] Book a room".to_string()),
+                    ..Section::new("Action Items")
+                },
+                Section::new("Decisions Made"),
+                Section {
+                    body: Some("Meet again next week.\n\nBring the numbers.".to_string()),
+                    ..Section::new("Next Steps")
+                },
+            ]),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_parse_markdown() {
+        let parsed = assert_round_trip(&metadata("md"));
+        let expected = metadata("md");
+        assert_eq!(parsed.filestem, expected.filestem);
+        assert_eq!(parsed.author, expected.author);
+        assert_eq!(parsed.datetime, expected.datetime);
+        assert_eq!(parsed.extra_values, expected.extra_values);
+        assert_eq!(parsed.header, expected.header);
+        assert_eq!(parsed.sections, expected.sections);
+        assert_eq!(parsed.footer, expected.footer);
+    }
+
+    #[test]
+    fn test_parse_text() {
+        let parsed = assert_round_trip(&metadata("txt"));
+        assert_eq!(parsed.sections, metadata("txt").sections);
+        assert_eq!(parsed.footer, metadata("txt").footer);
+    }
+
+    #[test]
+    fn test_parse_crlf() {
+        for extension in ["md", "txt"] {
+            let written = write(&metadata(extension));
+            let parsed = parse(&written.replace('\n', "\r\n"), Some(extension)).unwrap();
+            assert_eq!(write(&parsed), written);
+        }
+    }
+
+    #[test]
+    fn test_parse_org_asciidoc_rst() {
+        for extension in ["org", "adoc", "rst"] {
+            let parsed = assert_round_trip(&metadata(extension));
+            let expected = metadata(extension);
+            assert_eq!(parsed.filestem, expected.filestem, "{}", extension);
+            assert_eq!(parsed.author, expected.author, "{}", extension);
+            assert_eq!(parsed.datetime, expected.datetime, "{}", extension);
+            assert_eq!(parsed.extra_values, expected.extra_values, "{}", extension);
+            assert_eq!(parsed.header, expected.header, "{}", extension);
+            assert_eq!(parsed.sections, expected.sections, "{}", extension);
+            assert_eq!(parsed.footer, expected.footer, "{}", extension);
+        }
+    }
+
+    #[test]
+    fn test_parse_attribute_labels() {
+        let labeled = Metadata {
+            extra_values: vec![("meeting chair".to_string(), "Jane".to_string()), ("next: date".to_string(), "Monday".to_string())],
+            ..metadata("org")
+        };
+        let parsed = assert_round_trip(&labeled);
+        assert_eq!(parsed.extra_values[0].0, "meeting_chair");
+        let parsed = assert_round_trip(&Metadata { extension: Some("adoc".to_string()), ..labeled.clone() });
+        assert_eq!(parsed.extra_values[0].0, "meeting-chair");
+        let parsed = assert_round_trip(&Metadata { extension: Some("rst".to_string()), ..labeled.clone() });
+        assert_eq!(parsed.extra_values, labeled.extra_values);
+    }
+
+    #[test]
+    fn test_parse_korean_org_asciidoc_rst() {
+        for extension in ["org", "adoc", "rst"] {
+            let korean = Metadata {
+                filestem: "주간 회의".to_string(),
+                author: "김민수".to_string(),
+                extra_values: vec![("장소".to_string(), "3층 회의실".to_string())],
+                header: Some("회의 안건".to_string()),
+                sections: Some(vec![Section::new("결정 사항")]),
+                footer: Some("다음 회의".to_string()),
+                ..metadata(extension)
+            };
+            let parsed = assert_round_trip(&korean);
+            assert_eq!(parsed.filestem, korean.filestem, "{}", extension);
+            assert_eq!(parsed.extra_values, korean.extra_values, "{}", extension);
+            assert_eq!(parsed.sections, korean.sections, "{}", extension);
+        }
+    }
+
+    #[test]
+    fn test_parse_korean() {
+        let korean = Metadata {
+            filestem: "주간 회의".to_string(),
+            author: "김민수".to_string(),
+            header: Some("회의 안건".to_string()),
+            sections: Some(vec![Section::new("결정 사항")]),
+            footer: Some("다음 회의".to_string()),
+            ..metadata("txt")
+        };
+        assert!(write(&korean).contains("\n결정 사항\n---------\n"));
+        let parsed = assert_round_trip(&korean);
+        assert_eq!(parsed.sections, korean.sections);
+        assert_eq!(parsed.header, korean.header);
+    }
+
+    #[test]
+    fn test_parse_without_extension() {
+        let written = write(&metadata("md"));
+        let parsed = parse(&written, None).unwrap();
+        assert_eq!(parsed.extension.as_deref(), Some("md"));
+        assert_eq!(parsed.filestem, "Weekly sync");
+    }
+
+    #[test]
+    fn test_parse_minimal() {
+        let minimal = Metadata {
+            filestem: "test".to_string(),
+            author: "testuser".to_string(),
+            datetime: Local.with_ymd_and_hms(2024, 5, 2, 9, 30, 0).unwrap(),
+            ..Default::default()
+        };
+        let parsed = assert_round_trip(&minimal);
+        assert!(parsed.extra_values.is_empty());
+        assert!(parsed.header.is_none());
+        assert!(parsed.sections.is_none());
+        assert!(parsed.footer.is_none());
+    }
+
+    #[test]
+    fn test_parse_header_and_footer_without_sections() {
+        let mut metadata = metadata("md");
+        metadata.sections = None;
+        let parsed = assert_round_trip(&metadata);
+        assert_eq!(parsed.header, metadata.header);
+        assert_eq!(parsed.footer, metadata.footer);
+    }
+
+    #[test]
+    fn test_parse_last_section_without_body() {
+        let mut metadata = metadata("txt");
+        metadata.sections = Some(vec![Section::new("Action Items"), Section::new("Decisions Made")]);
+        metadata.footer = None;
+        let parsed = assert_round_trip(&metadata);
+        assert_eq!(parsed.sections, metadata.sections);
+    }
+
+    #[test]
+    fn test_parse_yaml_front_matter() {
+        let mut metadata = metadata("md");
+        metadata.front_matter = FrontMatter::Yaml;
+        metadata.datetime = Local::now();
+        metadata.extra_metadata = Some(vec![
+            ExtraField {
+                field_type: FieldType::People,
+                ..ExtraField::from("attendees")
+            },
+            ExtraField {
+                field_type: FieldType::Number,
+                ..ExtraField::from("duration")
+            },
+        ]);
+        metadata.extra_values.push(("attendees".to_string(), "John, Jane".to_string()));
+        metadata.extra_values.push(("duration".to_string(), "1.5".to_string()));
+        let parsed = assert_round_trip(&metadata);
+        assert_eq!(parsed.front_matter, FrontMatter::Yaml);
+        assert_eq!(parsed.datetime, metadata.datetime);
+        assert_eq!(parsed.extra_values, metadata.extra_values);
+        assert_eq!(parsed.extra_metadata, metadata.extra_metadata);
+    }
+
+    #[test]
+    fn test_parse_toml_front_matter() {
+        let mut metadata = metadata("md");
+        metadata.front_matter = FrontMatter::Toml;
+        metadata.extra_metadata = Some(vec![ExtraField {
+            field_type: FieldType::Boolean,
+            ..ExtraField::from("recorded")
+        }]);
+        metadata.extra_values.push(("recorded".to_string(), "true".to_string()));
+        let parsed = assert_round_trip(&metadata);
+        assert_eq!(parsed.front_matter, FrontMatter::Toml);
+        assert_eq!(parsed.filestem, metadata.filestem);
+        assert_eq!(parsed.extra_values, metadata.extra_values);
+    }
+
+    #[test]
+    fn test_parse_file() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("Weekly sync.md");
+        fs::write(&path, write(&metadata("md"))).unwrap();
+        let parsed = parse_file(&path).unwrap();
+        assert_eq!(parsed.extension.as_deref(), Some("md"));
+        assert_eq!(parsed.sections, metadata("md").sections);
+    }
+
+    #[test]
+    fn test_parse_invalid_document() {
+        assert!(parse("just some notes", None).is_err());
+        assert!(parse("title\n\nnot: metadata\n\n", None).is_err());
+        assert!(parse("---\ntitle: unclosed\n", None).is_err());
+    }
+}
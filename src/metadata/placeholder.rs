@@ -0,0 +1,204 @@
+//! Placeholder substitution for the header, footer, section bodies, filenames and output directories.
+//! The title of the document is never rendered, so a title with `{{` is kept as it is.
+//!
+//! A placeholder is a variable name surrounded by double braces, such as `{{author}}`.
+//! The following variables are supported:
+//!
+//! - `{{title}}`: The title of the document
+//! - `{{author}}`: The author of the document
+//! - `{{extension}}`: The extension of the document
+//! - `{{date}}`: The date the document was created, formatted as `%Y-%m-%d`
+//! - `{{date:<format>}}`: The date and time the document was created, formatted with the given
+//!   [chrono format](https://docs.rs/chrono/latest/chrono/format/strftime/index.html)
+//! - `{{time}}`: The time the document was created, formatted as `%H:%M:%S`
+//! - `{{datetime}}`: The date and time the document was created, formatted as `%Y-%m-%d %H:%M:%S`
+//! - `{{year}}`, `{{month}}`, `{{day}}`: The parts of the date the document was created
+//! - `{{weekday}}`: The day of the week the document was created, such as `Monday`
+//! - `{{extra.<label>}}`: The value of the extra metadata with the given label,
+//!   or an empty string if the label is configured but has no value
+//! - `{{env.<name>}}`: The value of the environment variable with the given name
+//! - `{{slug <variable>}}`: The value of the variable made safe for filenames with [`slugify`],
+//!   such as `{{slug title}}` or `{{slug extra.project}}`
+//!
+//! Unknown variables, undefined environment variables and unclosed placeholders are reported as errors.
+//!
+
+use std::env;
+use chrono::format::{Item, StrftimeItems};
+use crate::error::{Error, Result};
+use crate::metadata::metadata::Metadata;
+
+const OPEN: &str = "{{";
+const CLOSE: &str = "}}";
+
+/// Substitute every placeholder in the given text with its value from the given metadata.
+pub fn render(text: &str, metadata: &Metadata) -> Result<String> {
+    let mut rendered = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find(OPEN) {
+        rendered.push_str(&rest[..start]);
+        let after_open = &rest[start + OPEN.len()..];
+        let end = after_open
+            .find(CLOSE)
+            .ok_or_else(|| Error::Render(format!("Unclosed placeholder in \"{}\"", text)))?;
+        rendered.push_str(&resolve(after_open[..end].trim(), metadata).map_err(|e| Error::Render(e.to_string()))?);
+        rest = &after_open[end + CLOSE.len()..];
+    }
+    rendered.push_str(rest);
+    Ok(rendered)
+}
+
+/// Resolve the value of the given variable.
+fn resolve(variable: &str, metadata: &Metadata) -> std::result::Result<String, Box<dyn std::error::Error>> {
+    if let Some(variable) = variable.strip_prefix("slug ") {
+        return Ok(slugify(&resolve(variable.trim(), metadata)?));
+    }
+    if let Some(format) = variable.strip_prefix("date:") {
+        return format_datetime(metadata, format);
+    }
+    if let Some(label) = variable.strip_prefix("extra.") {
+        if let Some((_, value)) = metadata.extra_values.iter().find(|(key, _)| key == label) {
+            return Ok(value.clone());
+        }
+        return match &metadata.extra_metadata {
+            Some(extra_metadata) if extra_metadata.iter().any(|extra| extra.label == label) => Ok(String::new()),
+            _ => Err(format!("Unknown extra metadata \"{}\" in placeholder \"{{{{{}}}}}\"", label, variable).into()),
+        };
+    }
+    if let Some(name) = variable.strip_prefix("env.") {
+        return env::var(name).map_err(|_| {
+            format!("Environment variable \"{}\" in placeholder \"{{{{{}}}}}\" is not set", name, variable).into()
+        });
+    }
+    match variable {
+        "title" => Ok(metadata.filestem.clone()),
+        "author" => Ok(metadata.author.clone()),
+        "extension" => Ok(metadata.extension.clone().unwrap_or_default()),
+        "date" => format_datetime(metadata, "%Y-%m-%d"),
+        "time" => format_datetime(metadata, "%H:%M:%S"),
+        "datetime" => format_datetime(metadata, "%Y-%m-%d %H:%M:%S"),
+        "year" => format_datetime(metadata, "%Y"),
+        "month" => format_datetime(metadata, "%m"),
+        "day" => format_datetime(metadata, "%d"),
+        "weekday" => format_datetime(metadata, "%A"),
+        _ => Err(format!("Unknown variable in placeholder \"{{{{{}}}}}\"", variable).into()),
+    }
+}
+
+/// Make the given text safe for filenames, such as `weekly-sync` for `Weekly Sync!`.
+///
+/// Letters and digits of every script are kept, so Korean titles stay readable, and uppercase letters are lowercased.
+/// `_` is kept, and every run of other characters, including the characters that are illegal in filenames
+/// such as `/`, `:` and `?`, is replaced with a single `-`.
+pub fn slugify(text: &str) -> String {
+    let mut slug = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c.is_alphanumeric() || c == '_' {
+            true => slug.extend(c.to_lowercase()),
+            false if !slug.is_empty() && !slug.ends_with('-') => slug.push('-'),
+            false => (),
+        }
+    }
+    slug.trim_end_matches('-').to_string()
+}
+
+/// Format the datetime of the given metadata, rejecting invalid formats instead of panicking.
+fn format_datetime(metadata: &Metadata, format: &str) -> std::result::Result<String, Box<dyn std::error::Error>> {
+    let items: Vec<Item> = StrftimeItems::new(format).collect();
+    if items.iter().any(|item| matches!(item, Item::Error)) {
+        return Err(format!("Invalid date format \"{}\"", format).into());
+    }
+    Ok(metadata.datetime.format_with_items(items.into_iter()).to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Local, TimeZone};
+
+    fn metadata() -> Metadata {
+        Metadata {
+            filestem: "Weekly sync".to_string(),
+            author: "John Doe".to_string(),
+            datetime: Local.with_ymd_and_hms(2024, 5, 2, 9, 30, 0).unwrap(),
+            extension: Some("md".to_string()),
+            extra_metadata: Some(vec!["location".into(), "agenda".into()]),
+            extra_values: vec![("location".to_string(), "Seoul".to_string())],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_render_without_placeholders() {
+        assert_eq!(render("plain text", &metadata()).unwrap(), "plain text");
+    }
+
+    #[test]
+    fn test_render_metadata_fields() {
+        assert_eq!(
+            render("{{title}} by {{ author }} ({{extension}})", &metadata()).unwrap(),
+            "Weekly sync by John Doe (md)"
+        );
+    }
+
+    #[test]
+    fn test_render_dates() {
+        let metadata = metadata();
+        assert_eq!(render("{{date}}", &metadata).unwrap(), "2024-05-02");
+        assert_eq!(render("{{date:%d/%m/%Y %H:%M}}", &metadata).unwrap(), "02/05/2024 09:30");
+        assert_eq!(render("{{datetime}}", &metadata).unwrap(), "2024-05-02 09:30:00");
+        assert_eq!(render("{{year}}/{{month}}/{{day}}", &metadata).unwrap(), "2024/05/02");
+        assert_eq!(render("{{weekday}}", &metadata).unwrap(), "Thursday");
+    }
+
+    #[test]
+    fn test_render_invalid_date_format() {
+        assert!(render("{{date:%Q}}", &metadata()).is_err());
+    }
+
+    #[test]
+    fn test_render_extra() {
+        assert_eq!(render("at {{extra.location}}", &metadata()).unwrap(), "at Seoul");
+        assert_eq!(render("{{extra.agenda}}", &metadata()).unwrap(), "");
+        assert!(render("{{extra.attendees}}", &metadata()).is_err());
+    }
+
+    #[test]
+    fn test_render_env() {
+        let path = env::var("PATH").unwrap();
+        assert_eq!(render("{{env.PATH}}", &metadata()).unwrap(), path);
+        assert!(render("{{env.MOMI_SURELY_UNDEFINED_VARIABLE}}", &metadata()).is_err());
+    }
+
+    #[test]
+    fn test_slugify() {
+        assert_eq!(slugify("Weekly Sync"), "weekly-sync");
+        assert_eq!(slugify("  Q3 / Q4: planning?? "), "q3-q4-planning");
+        assert_eq!(slugify("주간 회의 (1차)"), "주간-회의-1차");
+        assert_eq!(slugify("release_v1.2"), "release_v1-2");
+        assert_eq!(slugify("<>:\"|*"), "");
+    }
+
+    #[test]
+    fn test_render_slug() {
+        let metadata = metadata();
+        assert_eq!(render("{{date:%Y-%m-%d}}_{{slug title}}", &metadata).unwrap(), "2024-05-02_weekly-sync");
+        assert_eq!(render("{{ slug extra.location }}", &metadata).unwrap(), "seoul");
+        assert!(render("{{slug project}}", &metadata).is_err());
+    }
+
+    #[test]
+    fn test_render_unknown_variable() {
+        let error = render("{{project}}", &metadata()).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Error rendering the document: Unknown variable in placeholder \"{{project}}\""
+        );
+        assert_eq!(error.exit_code(), 11);
+    }
+
+    #[test]
+    fn test_render_unclosed_placeholder() {
+        assert!(render("{{title", &metadata()).is_err());
+    }
+}
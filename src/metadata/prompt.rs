@@ -0,0 +1,158 @@
+//! Interactive prompt that asks for the values of the extra metadata.
+//!
+
+use std::io::{self, BufRead, Write};
+use crate::config::rich_metadata::{ExtraField, FieldType};
+
+/// The answer that skips a label, so it is not written to the document.
+pub const SKIP: &str = "-";
+
+/// Ask for the value of each of the given extra metadata on the given input.
+///
+/// The current value of each entry is offered as the default:
+/// - An empty answer keeps the default.
+/// - [`SKIP`] removes the entry, so it is not written to the document. Required fields cannot be skipped.
+/// - Any other answer replaces the value.
+///
+/// If the entry has a field in the given fields, its help text is shown
+/// and the answer is asked again until it is valid for the type of the field.
+/// If the input ends before every entry is asked, the remaining entries keep their defaults.
+pub fn ask_extra_values<R: BufRead, W: Write>(
+    extra_values: Vec<(String, String)>,
+    fields: &[ExtraField],
+    input: &mut R,
+    output: &mut W,
+) -> io::Result<Vec<(String, String)>> {
+    writeln!(
+        output,
+        "Enter the extra metadata. Press Enter to keep the default or type {} to skip.",
+        SKIP
+    )?;
+    let mut answered = Vec::with_capacity(extra_values.len());
+    for (label, default) in extra_values {
+        let field = fields.iter().find(|field| field.label == label);
+        if let Some(help) = field.and_then(|field| field.help.as_ref()) {
+            writeln!(output, "{}", help)?;
+        }
+        let prompt = match field {
+            Some(ExtraField { field_type: FieldType::Enum, values: Some(values), .. }) => {
+                format!("{} ({})", label, values.join("/"))
+            }
+            _ => label.clone(),
+        };
+        loop {
+            match default.is_empty() {
+                true => write!(output, "{}: ", prompt)?,
+                false => write!(output, "{} [{}]: ", prompt, default)?,
+            }
+            output.flush()?;
+            let mut answer = String::new();
+            if input.read_line(&mut answer)? == 0 {
+                answered.push((label, default));
+                break;
+            }
+            let answer = match answer.trim() {
+                "" => default.as_str(),
+                answer => answer,
+            };
+            if answer == SKIP {
+                match field.is_some_and(|field| field.required) {
+                    true => {
+                        writeln!(output, "\"{}\" is required and cannot be skipped", label)?;
+                        continue;
+                    }
+                    false => break,
+                }
+            }
+            match field.map(|field| field.validate(answer)) {
+                Some(Err(e)) => writeln!(output, "{}", e)?,
+                Some(Ok(value)) => {
+                    answered.push((label, value));
+                    break;
+                }
+                None => {
+                    answered.push((label, answer.to_string()));
+                    break;
+                }
+            }
+        }
+    }
+    Ok(answered)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn extra_values() -> Vec<(String, String)> {
+        vec![
+            ("location".to_string(), "Seoul".to_string()),
+            ("attendees".to_string(), "".to_string()),
+            ("agenda".to_string(), "".to_string()),
+        ]
+    }
+
+    #[test]
+    fn test_ask_extra_values() {
+        let mut input = "\nJohn, Jane\n-\n".as_bytes();
+        let mut output = vec![];
+        let answered = ask_extra_values(extra_values(), &[], &mut input, &mut output).unwrap();
+        assert_eq!(
+            answered,
+            vec![
+                ("location".to_string(), "Seoul".to_string()),
+                ("attendees".to_string(), "John, Jane".to_string()),
+            ]
+        );
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.contains("location [Seoul]: "));
+        assert!(output.contains("attendees: "));
+    }
+
+    #[test]
+    fn test_ask_extra_values_with_early_end_of_input() {
+        let mut input = "Busan\n".as_bytes();
+        let answered = ask_extra_values(extra_values(), &[], &mut input, &mut vec![]).unwrap();
+        assert_eq!(
+            answered,
+            vec![
+                ("location".to_string(), "Busan".to_string()),
+                ("attendees".to_string(), "".to_string()),
+                ("agenda".to_string(), "".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_ask_extra_values_until_valid() {
+        let fields = vec![
+            ExtraField {
+                field_type: FieldType::Date,
+                help: Some("The due date of the action items".to_string()),
+                ..ExtraField::from("due")
+            },
+            ExtraField {
+                required: true,
+                ..ExtraField::from("location")
+            },
+        ];
+        let extra_values = vec![
+            ("due".to_string(), "".to_string()),
+            ("location".to_string(), "".to_string()),
+        ];
+        let mut input = "tomorrow\n2024-05-02\n-\nSeoul\n".as_bytes();
+        let mut output = vec![];
+        let answered = ask_extra_values(extra_values, &fields, &mut input, &mut output).unwrap();
+        assert_eq!(
+            answered,
+            vec![
+                ("due".to_string(), "2024-05-02".to_string()),
+                ("location".to_string(), "Seoul".to_string()),
+            ]
+        );
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.contains("The due date of the action items"));
+        assert!(output.contains("\"due\" must be a date"));
+        assert!(output.contains("\"location\" is required and cannot be skipped"));
+    }
+}
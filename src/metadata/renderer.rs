@@ -0,0 +1,342 @@
+//! Renderers that write the document in the format given by its extension.
+//!
+//! - `.md`: Markdown, with a `# ` title and `## ` section headings
+//! - `.org`: Org-mode, with `#+TITLE:`, `#+AUTHOR:` and `#+DATE:` keywords and `* ` section headings
+//! - `.adoc`: AsciiDoc, with a `= ` title, document attributes and `== ` section headings
+//! - `.rst`: reStructuredText, with an overlined title, a docinfo field list and underlined section headings
+//! - Any other extension: plain text, with underlined section headings
+//!
+//! Markdown and plain text write the metadata as `key: value` lines or as front matter.
+//! Org-mode, AsciiDoc and reStructuredText always write it in their own syntax, so they cannot have front matter.
+//!
+
+use std::io::Write;
+use crate::error::{Error, Result};
+use crate::metadata::front_matter;
+use crate::metadata::metadata::Metadata;
+
+/// The format of the `created` metadata.
+const CREATED_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+/// A format the document can be written in.
+///
+/// The provided methods write plain text, so a format only overrides the parts it writes differently.
+pub trait Renderer {
+    /// Whether the metadata can be written as front matter instead of in the syntax of the format.
+    fn supports_front_matter(&self) -> bool {
+        true
+    }
+
+    /// The title line, followed by a blank line.
+    fn title(&self, title: &str) -> String {
+        format!("{}\n\n", title)
+    }
+
+    /// The title and the metadata of the document, followed by a blank line.
+    ///
+    /// The metadata is written as the front matter before the title if there is one,
+    /// and as `key: value` lines after the title otherwise.
+    fn document_header(&self, metadata: &Metadata) -> Result<String> {
+        let front_matter = front_matter::render(metadata, metadata.front_matter)?;
+        if !front_matter.is_empty() {
+            return Ok(format!("{}\n{}", front_matter, self.title(&metadata.filestem)));
+        }
+        let mut document_header = self.title(&metadata.filestem);
+        document_header.push_str(&format!(
+            "created: {}\nauthor: {}\n\n",
+            metadata.datetime.format(CREATED_FORMAT),
+            metadata.author
+        ));
+        if !metadata.extra_values.is_empty() {
+            for (label, value) in &metadata.extra_values {
+                document_header.push_str(&format!("{}: {}\n", label, value));
+            }
+            document_header.push('\n');
+        }
+        Ok(document_header)
+    }
+
+    /// The heading underlined with `-`, as long as the heading is wide, followed by a newline.
+    fn section_heading(&self, heading: &str) -> String {
+        format!("{}\n{}\n", heading, "-".repeat(display_width(heading)))
+    }
+
+    /// Write the whole document: the document header, the header, the sections and the footer.
+    fn render(&self, metadata: &Metadata, writer: &mut dyn Write) -> Result<()> {
+        writer.write_all(self.document_header(metadata)?.as_bytes())?;
+        writer.write_all(format!("{}\n\n", metadata.header.as_deref().unwrap_or("")).as_bytes())?;
+        for section in metadata.sections.iter().flatten() {
+            writer.write_all(self.section_heading(&section.heading).as_bytes())?;
+            writer.write_all(b"\n")?;
+            if let Some(body) = &section.body {
+                writer.write_all(format!("{}\n\n", body).as_bytes())?;
+            }
+        }
+        writer.write_all(metadata.footer.as_deref().unwrap_or("").as_bytes())?;
+        writer.flush()?;
+        Ok(())
+    }
+}
+
+/// The renderer of the given extension. Unknown extensions are plain text.
+pub fn for_extension(extension: Option<&str>) -> Box<dyn Renderer> {
+    match extension {
+        Some("md") => Box::new(Markdown),
+        Some("org") => Box::new(Org),
+        Some("adoc") => Box::new(AsciiDoc),
+        Some("rst") => Box::new(ReStructuredText),
+        _ => Box::new(Text),
+    }
+}
+
+/// Plain text.
+pub struct Text;
+
+impl Renderer for Text {}
+
+/// Markdown.
+pub struct Markdown;
+
+impl Renderer for Markdown {
+    fn title(&self, title: &str) -> String {
+        format!("# {}\n\n", title)
+    }
+
+    fn section_heading(&self, heading: &str) -> String {
+        format!("## {}\n", heading)
+    }
+}
+
+/// Org-mode.
+pub struct Org;
+
+impl Renderer for Org {
+    fn supports_front_matter(&self) -> bool {
+        false
+    }
+
+    /// The `#+TITLE:`, `#+AUTHOR:` and `#+DATE:` keywords, and a keyword for every extra metadata,
+    /// such as `#+MEETING_CHAIR:` for `meeting chair`.
+    fn document_header(&self, metadata: &Metadata) -> Result<String> {
+        let mut document_header = format!(
+            "#+TITLE: {}\n#+AUTHOR: {}\n#+DATE: {}\n",
+            metadata.filestem,
+            metadata.author,
+            metadata.datetime.format("<%Y-%m-%d %a %H:%M>")
+        );
+        for (label, value) in &metadata.extra_values {
+            let name = attribute_name(label, '_', &["TITLE", "AUTHOR", "DATE"])?;
+            document_header.push_str(&format!("#+{}: {}\n", name.to_uppercase(), value));
+        }
+        document_header.push('\n');
+        Ok(document_header)
+    }
+
+    fn section_heading(&self, heading: &str) -> String {
+        format!("* {}\n", heading)
+    }
+}
+
+/// AsciiDoc.
+pub struct AsciiDoc;
+
+impl Renderer for AsciiDoc {
+    fn supports_front_matter(&self) -> bool {
+        false
+    }
+
+    /// The document title followed by the `:author:` and `:revdate:` attributes,
+    /// and an attribute for every extra metadata, such as `:meeting-chair:` for `meeting chair`.
+    fn document_header(&self, metadata: &Metadata) -> Result<String> {
+        let mut document_header = format!(
+            "= {}\n:author: {}\n:revdate: {}\n",
+            metadata.filestem,
+            metadata.author,
+            metadata.datetime.format(CREATED_FORMAT)
+        );
+        for (label, value) in &metadata.extra_values {
+            let name = attribute_name(label, '-', &["author", "revdate"])?;
+            document_header.push_str(&format!(":{}: {}\n", name.to_lowercase(), value));
+        }
+        document_header.push('\n');
+        Ok(document_header)
+    }
+
+    fn section_heading(&self, heading: &str) -> String {
+        format!("== {}\n", heading)
+    }
+}
+
+/// reStructuredText.
+pub struct ReStructuredText;
+
+impl Renderer for ReStructuredText {
+    fn supports_front_matter(&self) -> bool {
+        false
+    }
+
+    /// The title between `=` lines followed by a docinfo field list with `:Author:`, `:Date:`
+    /// and a field for every extra metadata, with `\` and `:` in its label escaped with `\`.
+    fn document_header(&self, metadata: &Metadata) -> Result<String> {
+        let line = "=".repeat(display_width(&metadata.filestem));
+        let mut document_header = format!(
+            "{}\n{}\n{}\n\n:Author: {}\n:Date: {}\n",
+            line,
+            metadata.filestem,
+            line,
+            metadata.author,
+            metadata.datetime.format(CREATED_FORMAT)
+        );
+        for (label, value) in &metadata.extra_values {
+            document_header.push_str(&format!(":{}: {}\n", field_name(label)?, value));
+        }
+        document_header.push('\n');
+        Ok(document_header)
+    }
+
+    /// The heading underlined with `-`, as long as the heading is wide.
+    fn section_heading(&self, heading: &str) -> String {
+        format!("{}\n{}\n", heading, "-".repeat(display_width(heading)))
+    }
+}
+
+/// The name of a keyword or attribute for the given label, with every run of other characters
+/// than letters, digits, `-` and `_` replaced with the given separator.
+///
+/// A label without letters or digits, such as `???`, and a label whose name is one of the given standard names
+/// regardless of case, such as `date` in Org-mode, are an [`Error::InvalidField`].
+fn attribute_name(label: &str, separator: char, standard: &[&str]) -> Result<String> {
+    let mut name = String::with_capacity(label.len());
+    for c in label.trim().chars() {
+        match c.is_alphanumeric() || c == '-' || c == '_' {
+            true => name.push(c),
+            false if !name.ends_with(separator) => name.push(separator),
+            false => (),
+        }
+    }
+    let name = name.trim_end_matches(separator).to_string();
+    if !name.chars().any(char::is_alphanumeric) {
+        return Err(Error::InvalidField(format!(
+            "The label \"{}\" has no letters or digits to name a keyword or attribute",
+            label
+        )));
+    }
+    if standard.iter().any(|standard| standard.eq_ignore_ascii_case(&name)) {
+        return Err(Error::InvalidField(format!("The label \"{}\" is the name of a standard keyword or attribute", label)));
+    }
+    Ok(name)
+}
+
+/// The name of a reStructuredText field for the given label, with `\` and `:` escaped with `\`.
+/// An empty label and the labels of the standard fields, `Author` and `Date` regardless of case, are an [`Error::InvalidField`].
+fn field_name(label: &str) -> Result<String> {
+    if label.trim().is_empty() || ["author", "date"].iter().any(|standard| standard.eq_ignore_ascii_case(label.trim())) {
+        return Err(Error::InvalidField(format!("The label \"{}\" cannot be a reStructuredText field", label)));
+    }
+    Ok(label.replace('\\', "\\\\").replace(':', "\\:"))
+}
+
+/// The number of columns the given text takes in a terminal, where wide characters such as Korean take two.
+pub(crate) fn display_width(text: &str) -> usize {
+    text.chars()
+        .map(|c| match c as u32 {
+            0x1100..=0x115F | 0x2E80..=0xA4CF | 0xAC00..=0xD7A3 | 0xF900..=0xFAFF | 0xFE30..=0xFE4F
+            | 0xFF00..=0xFF60 | 0xFFE0..=0xFFE6 => 2,
+            _ => 1,
+        })
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Local, TimeZone};
+    use crate::config::section::Section;
+
+    fn metadata(extension: &str) -> Metadata {
+        Metadata {
+            filestem: "Weekly sync".to_string(),
+            author: "John Doe".to_string(),
+            datetime: Local.with_ymd_and_hms(2024, 5, 2, 9, 30, 0).unwrap(),
+            extension: Some(extension.to_string()),
+            header: Some("header".to_string()),
+            footer: Some("footer".to_string()),
+            extra_values: vec![("meeting chair".to_string(), "Jane Doe".to_string())],
+            sections: Some(vec![Section {
+                body: Some("- [ ] ".to_string()),
+                ..Section::new("Action Items")
+            }]),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_render_org() {
+        assert_eq!(
+            metadata("org").to_doc_string().unwrap(),
+            "#+TITLE: Weekly sync\n#+AUTHOR: John Doe\n#+DATE: <2024-05-02 Thu 09:30>\n#+MEETING_CHAIR: Jane Doe\n\n\
+            header\n\n\
+            * Action Items\n\n- [ ] \n\n\
+            footer"
+        );
+    }
+
+    #[test]
+    fn test_render_asciidoc() {
+        assert_eq!(
+            metadata("adoc").to_doc_string().unwrap(),
+            "= Weekly sync\n:author: John Doe\n:revdate: 2024-05-02 09:30:00\n:meeting-chair: Jane Doe\n\n\
+            header\n\n\
+            == Action Items\n\n- [ ] \n\n\
+            footer"
+        );
+    }
+
+    #[test]
+    fn test_render_rst() {
+        assert_eq!(
+            metadata("rst").to_doc_string().unwrap(),
+            "===========\nWeekly sync\n===========\n\n:Author: John Doe\n:Date: 2024-05-02 09:30:00\n:meeting chair: Jane Doe\n\n\
+            header\n\n\
+            Action Items\n------------\n\n- [ ] \n\n\
+            footer"
+        );
+    }
+
+    #[test]
+    fn test_display_width() {
+        assert_eq!(display_width("Weekly"), 6);
+        assert_eq!(display_width("주간 회의"), 9);
+        assert_eq!(ReStructuredText.section_heading("회의"), "회의\n----\n");
+        assert_eq!(Text.section_heading("회의"), "회의\n----\n");
+    }
+
+    #[test]
+    fn test_attribute_name() {
+        assert_eq!(attribute_name("meeting chair", '-', &[]).unwrap(), "meeting-chair");
+        assert_eq!(attribute_name(" next: date ", '_', &[]).unwrap(), "next_date");
+        for label in ["???", " : ", "-", "__"] {
+            assert!(matches!(attribute_name(label, '_', &[]), Err(Error::InvalidField(_))), "{}", label);
+        }
+        assert!(matches!(attribute_name("Date", '_', &["TITLE", "AUTHOR", "DATE"]), Err(Error::InvalidField(_))));
+    }
+
+    #[test]
+    fn test_render_with_symbol_label() {
+        for extension in ["org", "adoc"] {
+            let metadata = Metadata { extra_values: vec![("???".to_string(), "x".to_string())], ..metadata(extension) };
+            assert!(matches!(metadata.to_doc_string(), Err(Error::InvalidField(_))), "{}", extension);
+        }
+    }
+
+    #[test]
+    fn test_field_name() {
+        assert_eq!(field_name("meeting chair").unwrap(), "meeting chair");
+        assert_eq!(field_name("next: date").unwrap(), "next\\: date");
+        assert_eq!(field_name("a\\b").unwrap(), "a\\\\b");
+        assert!(matches!(field_name(" "), Err(Error::InvalidField(_))));
+        assert!(matches!(field_name("date"), Err(Error::InvalidField(_))));
+        let metadata = Metadata { extra_values: vec![("next: date".to_string(), "Monday".to_string())], ..metadata("rst") };
+        assert!(metadata.to_doc_string().unwrap().contains("\n:next\\: date: Monday\n"));
+    }
+}
@@ -0,0 +1,88 @@
+//! The values used by the last run, kept in a state file separately from the config file.
+//!
+//! The state file is only written, never read as defaults,
+//! so a one-off value such as `-a "Guest"` does not change the config file.
+//! Use `--remember` or `momi config set` to change the defaults.
+//!
+
+use std::fs;
+use std::path::Path;
+use serde_derive::{Deserialize, Serialize};
+use crate::config::front_matter::FrontMatter;
+use crate::error::{Error, Result};
+use crate::metadata::metadata::Metadata;
+
+/// The name of the state file, kept in the user config directory whichever config file is used,
+/// or next to the executable if there is no user config directory.
+pub const STATE_FILE: &str = "state.json";
+
+/// The values used by the last run.
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct State {
+    /// The title of the last document
+    pub filestem: String,
+    /// The author of the last document
+    pub author: String,
+    /// The date and time the last document was created, in RFC 3339
+    pub created: String,
+    /// The extension of the last document
+    pub extension: Option<String>,
+    /// The template used for the last document
+    pub template: Option<String>,
+    /// The format of the front matter of the last document
+    pub front_matter: FrontMatter,
+    /// The labels and values of the extra metadata of the last document
+    pub extra_values: Vec<(String, String)>,
+}
+
+impl From<&Metadata> for State {
+    fn from(metadata: &Metadata) -> Self {
+        State {
+            filestem: metadata.filestem.clone(),
+            author: metadata.author.clone(),
+            created: metadata.datetime.to_rfc3339(),
+            extension: metadata.extension.clone(),
+            template: metadata.template.clone(),
+            front_matter: metadata.front_matter,
+            extra_values: metadata.extra_values.clone(),
+        }
+    }
+}
+
+impl State {
+    pub fn to_file<P: AsRef<Path>>(&self, state_file: P) -> Result<()> {
+        let state_file = state_file.as_ref();
+        let state_json = serde_json::to_string_pretty(self).map_err(|e| Error::ConfigParse(e.to_string()))?;
+        fs::write(state_file, state_json).map_err(|source| Error::ConfigIo { path: state_file.to_path_buf(), source })
+    }
+
+    pub fn from_file<P: AsRef<Path>>(state_file: P) -> Result<Self> {
+        let state_file = state_file.as_ref();
+        let state_json = fs::read_to_string(state_file)
+            .map_err(|source| Error::ConfigIo { path: state_file.to_path_buf(), source })?;
+        serde_json::from_str(&state_json).map_err(|e| Error::ConfigParse(format!("{}: {}", state_file.display(), e)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_to_file_and_from_file() {
+        let dir = tempdir().unwrap();
+        let state_file = dir.path().join(STATE_FILE);
+        let metadata = Metadata {
+            filestem: "Weekly".to_string(),
+            author: "Guest".to_string(),
+            extension: Some("md".to_string()),
+            extra_values: vec![("location".to_string(), "Seoul".to_string())],
+            ..Default::default()
+        };
+        let state = State::from(&metadata);
+        state.to_file(&state_file).unwrap();
+        assert_eq!(State::from_file(&state_file).unwrap(), state);
+        assert_eq!(state.author, "Guest");
+    }
+}
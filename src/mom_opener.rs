@@ -1,17 +1,98 @@
+use std::fs;
+use std::path::{Path, PathBuf};
 use log::{error, info};
-use opener;
+use crate::error::{Error, Result};
 
 pub struct Moms {
     pub moms: Vec<String>,
 }
 
 impl Moms {
-    pub fn open_all(&self) {
+    /// Collect the files in the given directory and its subdirectories, sorted by their paths,
+    /// so the minutes written to an output directory such as `minutes/{{year}}/{{month}}` are found.
+    /// Hidden directories such as `.momi` and `.git` and symbolic links to directories are not searched.
+    pub fn from_dir<P: AsRef<Path>>(dir: P) -> Result<Self> {
+        let mut moms = vec![];
+        collect_files(dir.as_ref(), &mut moms)?;
+        moms.sort();
+        Ok(Moms { moms })
+    }
+
+    /// Open every file with the default program.
+    /// All files are tried, and the error of the last file that could not be opened is returned.
+    pub fn open_all(&self) -> Result<()> {
+        let mut result = Ok(());
         for mom in &self.moms {
             match opener::open(mom) {
                 Ok(_) => info!("Opened {:?}", mom),
-                Err(e) => error!("Error opening {:?}: {:?}", mom, e),
+                Err(e) => {
+                    let e = Error::Open { path: PathBuf::from(mom), message: e.to_string() };
+                    error!("{}", e);
+                    result = Err(e);
+                }
+            }
+        }
+        result
+    }
+}
+
+/// Collect the files in the given directory and its subdirectories into the given list.
+fn collect_files(dir: &Path, moms: &mut Vec<String>) -> Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let path = entry.path();
+        if entry.file_type()?.is_dir() {
+            if !entry.file_name().to_string_lossy().starts_with('.') {
+                collect_files(&path, moms)?;
+            }
+        } else if path.is_file() {
+            if let Some(path) = path.to_str() {
+                moms.push(path.to_string());
             }
         }
     }
-}
\ No newline at end of file
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_from_dir() {
+        let dir = tempdir().unwrap();
+        fs::write(dir.path().join("b.md"), "").unwrap();
+        fs::write(dir.path().join("a.txt"), "").unwrap();
+        fs::create_dir(dir.path().join("templates")).unwrap();
+        let moms = Moms::from_dir(dir.path()).unwrap();
+        assert_eq!(
+            moms.moms,
+            vec![
+                dir.path().join("a.txt").to_str().unwrap().to_string(),
+                dir.path().join("b.md").to_str().unwrap().to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_from_dir_with_year_and_month() {
+        let dir = tempdir().unwrap();
+        let month = dir.path().join("2024").join("05");
+        fs::create_dir_all(&month).unwrap();
+        fs::create_dir(dir.path().join(".momi")).unwrap();
+        fs::write(month.join("sync.md"), "").unwrap();
+        fs::write(dir.path().join("2024").join("review.md"), "").unwrap();
+        fs::write(dir.path().join(".momi").join("config.json"), "").unwrap();
+        fs::write(dir.path().join("notes.txt"), "").unwrap();
+        let moms = Moms::from_dir(dir.path()).unwrap();
+        assert_eq!(
+            moms.moms,
+            vec![
+                month.join("sync.md").to_str().unwrap().to_string(),
+                dir.path().join("2024").join("review.md").to_str().unwrap().to_string(),
+                dir.path().join("notes.txt").to_str().unwrap().to_string(),
+            ]
+        );
+    }
+}